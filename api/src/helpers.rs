@@ -0,0 +1,239 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::ErrorCode;
+use common::notifier::Notifier;
+use common::migrate::migrate_player_ids;
+use common::protocol::ServerMessage;
+use common::secret::migrate_secrets;
+use common::store::{ConnectionEntry, GameStore, StoreError};
+
+use crate::ActionError;
+
+/// Send `reply` to the client, tagged with the ID of the request it answers.
+pub fn send_reply(notifier: &dyn Notifier, connection_id: &str, request_id: Option<&str>, reply: &ServerMessage) {
+    if let Err(e) = notifier.send(connection_id, &reply.reply_to(request_id)) {
+        error!("Error sending reply: {:?}", e)
+    }
+}
+
+/// Store a brand new game, returning false if its lobby ID is already taken.
+pub fn create_state(store: &dyn GameStore, game_state: &common::GameState) -> Result<bool, ActionError> {
+    match store.create(game_state) {
+        Ok(()) => Ok(true),
+        Err(StoreError::AlreadyExists) => Ok(false),
+        Err(err) => {
+            error!("Failed to perform new game connection operation: {:?}", err);
+            Err(ActionError::new(ErrorCode::InternalError, "Error creating game, please try again"))
+        },
+    }
+}
+
+/// The lobby an action from `connection_id` is for: the `code` the client
+/// named, or else the lobby the connection last joined.
+pub fn lobby_for(store: &dyn GameStore, connection_id: &str, code: Option<String>) -> Result<String, ActionError> {
+    if let Some(code) = code {
+        return Ok(code);
+    }
+    match store.find_connection(connection_id) {
+        Ok(Some(entry)) => Ok(entry.lobby_id),
+        Ok(None) => Err(ActionError::new(ErrorCode::LobbyNotFound, "Not in a game, please join one first")),
+        Err(err) => {
+            error!("Error finding game for connection {}: {:?}", connection_id, err);
+            Err(ActionError::new(ErrorCode::InternalError, "Error fetching lobby"))
+        },
+    }
+}
+
+/// Record that `connection_id` now plays as `player_id` in `game_state`. The
+/// action has already been applied, so a failure is only logged; the client
+/// can still name its lobby explicitly.
+pub fn bind_connection(store: &dyn GameStore, connection_id: &str, game_state: &common::GameState, player_id: &str) {
+    let entry = ConnectionEntry {
+        connection_id: connection_id.to_string(),
+        lobby_id: game_state.lobby_id.clone(),
+        player_id: player_id.to_string(),
+        ttl: game_state.ttl,
+    };
+    if let Err(err) = store.bind_connection(&entry) {
+        error!("Error binding connection {} to lobby {}: {:?}", connection_id, game_state.lobby_id, err);
+    }
+}
+
+/// The view of `game_state` belonging to the player on `connection_id`.
+pub fn player_state(game_state: &common::GameState, connection_id: &str) -> Result<ServerMessage, ActionError> {
+    match game_state.players.iter().find(|p| p.connection_id == connection_id) {
+        Some(player) => Ok(common::broadcast::player_state(player, game_state)),
+        None => Err(ActionError::new(ErrorCode::PlayerNotFound, "You are not in this game")),
+    }
+}
+
+/// How many applied request IDs each lobby remembers for spotting resends.
+const RECENT_REQUESTS: usize = 50;
+
+/// Key in `GameState.internal_state` holding the recently applied request IDs,
+/// as a JSON list of `[key, action, state_version]` triples, oldest first.
+const RECENT_REQUESTS_KEY: &str = "recent_requests";
+
+/// How many times an action is applied to a freshly loaded state before
+/// giving up when other requests keep saving the game first, and how many
+/// lobby codes a new game tries before giving up on finding a free one.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// A request the client tagged with `id`, asking for the action named `action`.
+#[derive(Debug, Clone, Copy)]
+pub struct Request<'a> {
+    pub id: &'a str,
+    pub action: &'a str,
+}
+
+/// Load the game, apply `action` to it and save the result. If another
+/// request saved the game in the meantime the action is re-applied to the
+/// newer state, so it only fails if it is no longer valid. Returns the
+/// version the game was saved at along with the saved state.
+///
+/// When the client tagged the action as `request` and the game has already
+/// applied that request from the same player, the action is not applied
+/// again and the version it originally produced is returned instead, with
+/// the current state. Players are recognised by their permanent ID, so this
+/// holds even if the request was resent after reconnecting.
+pub fn apply_action(store: &dyn GameStore, lobby_id: String, actor: &str, request: Option<Request>, action: engine::Action)
+        -> Result<(u32, common::GameState), ActionError> {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let game_state = get_state(store, lobby_id.clone())?;
+        let caller = caller_id(&game_state, actor, &action);
+        let applied = match (request, &caller) {
+            (Some(request), Some(caller)) => applied_version(&game_state, caller, request)?,
+            _ => None,
+        };
+        let game_state = match applied {
+            Some(version) if game_state.players.iter().any(|p| Some(&p.id) == caller.as_ref() && p.connection_id == actor) => {
+                info!("Request {:?} already applied to lobby {}", request.map(|r| r.id), lobby_id);
+                return Ok((version, game_state));
+            },
+            // Only a rejoin can be resent from a connection its player is not
+            // on yet, and it still has to move them onto it.
+            Some(_) => {
+                let mut game_state = engine::apply(game_state, actor, action.clone())?;
+                mark_seen(&mut game_state, actor, &action);
+                game_state
+            },
+            None => {
+                let mut game_state = engine::apply(game_state, actor, action.clone())?;
+                mark_seen(&mut game_state, actor, &action);
+                if let (Some(request), Some(caller)) = (request, caller_id(&game_state, actor, &action)) {
+                    // Saving bumps the version by one, which is what the ack reports.
+                    let version = game_state.version + 1;
+                    record_request(&mut game_state, &caller, request, version);
+                }
+                game_state
+            },
+        };
+        match store.save(game_state) {
+            Ok(saved) => return Ok((applied.unwrap_or(saved.version), saved)),
+            Err(StoreError::Conflict) => warn!("Conflict saving lobby {} on attempt {}", lobby_id, attempt),
+            Err(err) => {
+                error!("Error saving state, please try again: {:?}", err);
+                return Err(ActionError::new(ErrorCode::InternalError, "Error saving state, please try again"));
+            },
+        }
+    }
+    error!("Gave up saving lobby {} after {} attempts", lobby_id, MAX_ATTEMPTS);
+    Err(ActionError::new(ErrorCode::StateConflict, "Error saving state, please try again"))
+}
+
+/// The permanent ID of the player taking `action` from `actor`, if they are
+/// in `game_state`.
+fn caller_id(game_state: &common::GameState, actor: &str, action: &engine::Action) -> Option<String> {
+    match action {
+        engine::Action::Rejoin { player } => Some(player.clone()),
+        _ => game_state.players.iter().find(|p| p.connection_id == actor).map(|p| p.id.clone()),
+    }
+}
+
+/// Record that the player who took `action` from `actor` was last seen now.
+fn mark_seen(game_state: &mut common::GameState, actor: &str, action: &engine::Action) {
+    let caller = caller_id(game_state, actor, action);
+    if let Some(player) = game_state.players.iter_mut().find(|p| Some(&p.id) == caller.as_ref()) {
+        player.last_seen = now();
+    }
+}
+
+/// How a request from `player_id` is remembered among the lobby's recent requests.
+fn request_key(player_id: &str, request_id: &str) -> String {
+    format!("{}:{}", player_id, request_id)
+}
+
+/// The version that `request` from `player_id` produced, if it is among the
+/// recent requests. Reusing a request ID for a different action is an error,
+/// rather than a resend.
+pub fn applied_version(game_state: &common::GameState, player_id: &str, request: Request) -> Result<Option<u32>, ActionError> {
+    let key = request_key(player_id, request.id);
+    match recent_requests(game_state).into_iter().find(|(k, _, _)| *k == key) {
+        Some((_, action, version)) if action == request.action => Ok(Some(version)),
+        Some((_, action, _)) => Err(ActionError::new(ErrorCode::InvalidRequest,
+            &format!("Request ID {:?} was already used for {}", request.id, action))),
+        None => Ok(None),
+    }
+}
+
+/// Remember that `request` from `player_id` produced `version`, forgetting
+/// the oldest requests beyond the last `RECENT_REQUESTS`.
+pub fn record_request(game_state: &mut common::GameState, player_id: &str, request: Request, version: u32) {
+    let mut recent = recent_requests(game_state);
+    recent.push((request_key(player_id, request.id), request.action.to_string(), version));
+    let skip = recent.len().saturating_sub(RECENT_REQUESTS);
+    let recent: Vec<_> = recent.into_iter().skip(skip).collect();
+    game_state.internal_state.insert(RECENT_REQUESTS_KEY.to_string(), serde_json::to_string(&recent).unwrap());
+}
+
+fn recent_requests(game_state: &common::GameState) -> Vec<(String, String, u32)> {
+    game_state.internal_state.get(RECENT_REQUESTS_KEY)
+        .and_then(|recent| serde_json::from_str(recent).ok())
+        .unwrap_or_default()
+}
+
+/// Load the game, upgrading anything stored by older versions. An upgraded
+/// game is saved straight away, so the upgrade, which hashes any plaintext
+/// secrets, is only done once however the game is used next.
+pub fn get_state(store: &dyn GameStore, lobby_id: String) -> Result<common::GameState, ActionError> {
+    match store.load(&lobby_id) {
+        Ok(mut gs) => {
+            let hashed = migrate_secrets(&mut gs);
+            if hashed {
+                info!("Hashed plaintext secrets in lobby {}", lobby_id);
+            }
+            let assigned = migrate_player_ids(&mut gs);
+            if assigned {
+                info!("Assigned player IDs in lobby {}", lobby_id);
+            }
+            if !hashed && !assigned {
+                return Ok(gs);
+            }
+            match store.save(gs.clone()) {
+                Ok(saved) => Ok(saved),
+                Err(err) => {
+                    // Whoever saved first may not have upgraded the game; it
+                    // is upgraded again on the next load.
+                    warn!("Error saving upgraded lobby {}: {:?}", lobby_id, err);
+                    Ok(gs)
+                },
+            }
+        },
+        Err(StoreError::NotFound) => Err(ActionError::new(ErrorCode::LobbyNotFound, "Game not found")),
+        Err(StoreError::Corrupted(e)) => {
+            error!("Game state corrupted: {}", e);
+            Err(ActionError::new(ErrorCode::InternalError, "Game state corrupted"))
+        },
+        Err(e) => {
+            error!("Error fetching lobby: {:?}", e);
+            Err(ActionError::new(ErrorCode::InternalError, "Error fetching lobby"))
+        },
+    }
+}
+
+/// The current time in seconds since the epoch.
+pub fn now() -> u32 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs() as u32
+}
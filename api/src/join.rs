@@ -0,0 +1,106 @@
+use rand::Rng;
+
+use common::ErrorCode;
+use common::protocol::ServerMessage;
+use common::secret::{hash_secret, verify_secret};
+use common::session::SessionSigner;
+use common::store::GameStore;
+
+use crate::ActionError;
+use crate::helpers::{create_state, apply_action, applied_version, bind_connection, get_state, now, player_state, record_request, Request,
+    MAX_ATTEMPTS};
+
+/// Join the lobby `code`, or start a new one, returning the resulting state
+/// version and a session token the player can later rejoin with. A player
+/// joining under a name already in the lobby takes it back if their secret
+/// matches, and is also returned their view of the game, as after any rejoin.
+pub fn handle_join(store: &dyn GameStore, signer: &SessionSigner, connection_id: &str, request: Option<Request>,
+        name: String, secret: String, code: Option<String>) -> Result<(u32, String, Option<ServerMessage>), ActionError> {
+    let mut rejoined = false;
+    let (state_version, game_state) = match code {
+        None => match request.map(|request| started_game(store, connection_id, request)).transpose()?.flatten() {
+            Some(started) => started,
+            None => new_game(store, connection_id, request, name.clone(), hash(secret))?,
+        },
+        Some(code) => {
+            let game_state = get_state(store, code.clone())?;
+            let action = match game_state.players.iter().find(|p| p.name == name) {
+                Some(player) if verify_secret(&player.secret, &secret) => {
+                    // A resend of the join that added the player is answered
+                    // as that join was, not as a rejoin.
+                    rejoined = match request {
+                        Some(request) => applied_version(&game_state, &player.id, request)?.is_none(),
+                        None => true,
+                    };
+                    engine::Action::Rejoin { player: player.id.clone() }
+                },
+                Some(_) => {
+                    warn!("Non-matching secret on rejoin to lobby {}", code);
+                    return Err(ActionError::new(ErrorCode::InvalidPlayer, "That name is taken and the secret does not match"));
+                },
+                None => engine::Action::Join { name: name.clone(), secret: hash(secret) },
+            };
+            apply_action(store, code, connection_id, request, action)?
+        },
+    };
+    let player = game_state.players.iter().find(|p| p.name == name)
+        .ok_or_else(|| ActionError::new(ErrorCode::InternalError, "Joined player is missing"))?;
+    bind_connection(store, connection_id, &game_state, &player.id);
+    let view = if rejoined { Some(player_state(&game_state, connection_id)?) } else { None };
+    Ok((state_version, signer.issue(&game_state.lobby_id, &player.id), view))
+}
+
+/// Start a new lobby under a random code, trying another whenever the code
+/// is taken. When the client tagged the join as `request`, the lobby
+/// remembers it so that a resend from the same connection does not start
+/// another.
+fn new_game(store: &dyn GameStore, connection_id: &str, request: Option<Request>, name: String, secret: String)
+        -> Result<(u32, common::GameState), ActionError> {
+    let ttl = now() + (48*60*60);
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut game_state = engine::new_game(create_random_code(), connection_id, name.clone(), secret.clone(), ttl)?;
+        game_state.players[0].last_seen = now();
+        if let Some(request) = request {
+            let (player_id, version) = (game_state.players[0].id.clone(), game_state.version);
+            record_request(&mut game_state, &player_id, request, version);
+        }
+        if create_state(store, &game_state)? {
+            return Ok((game_state.version, game_state));
+        }
+        warn!("Lobby {} already exists on attempt {}", game_state.lobby_id, attempt);
+    }
+    error!("Gave up finding a free lobby code after {} attempts", MAX_ATTEMPTS);
+    Err(ActionError::new(ErrorCode::StateConflict, "Error creating game, please try again"))
+}
+
+/// The lobby that the join `request` from `connection_id` already started,
+/// with the version it was started at.
+fn started_game(store: &dyn GameStore, connection_id: &str, request: Request)
+        -> Result<Option<(u32, common::GameState)>, ActionError> {
+    let entry = match store.find_connection(connection_id) {
+        Ok(Some(entry)) => entry,
+        _ => return Ok(None),
+    };
+    let game_state = match get_state(store, entry.lobby_id) {
+        Ok(game_state) => game_state,
+        Err(_) => return Ok(None),
+    };
+    let started = applied_version(&game_state, &entry.player_id, request)?;
+    if started.is_some() {
+        info!("Request {:?} already started lobby {}", request.id, game_state.lobby_id);
+    }
+    Ok(started.map(|version| (version, game_state)))
+}
+
+/// Hash a new player's `secret` before it reaches the rules. An empty secret
+/// is passed on as it is, for the rules to reject.
+fn hash(secret: String) -> String {
+    if secret.is_empty() { secret } else { hash_secret(&secret) }
+}
+
+fn create_random_code() -> String {
+    let mut rng = rand::thread_rng();
+    let valid_code_chars = vec!["A","B","C","D","E","F","G","H","I","J","K","L","M","N","O","P","Q","R","S","T","U","V","W","X","Y","Z"];
+    (0..4).map(|_| (valid_code_chars[rng.gen_range(0, 26) as usize]).to_owned()).collect()
+}
@@ -0,0 +1,656 @@
+#[macro_use]
+extern crate log;
+extern crate rand;
+
+use std::fmt;
+use std::error::Error;
+
+use common::ErrorCode;
+use common::notifier::Notifier;
+use common::protocol::{parse_client_message, AckReply, ClientMessage, ErrorReply, ProtocolError, ServerMessage};
+use common::redact::redact_body;
+use common::session::SessionSigner;
+use common::store::GameStore;
+
+mod join;
+use join::handle_join;
+
+mod helpers;
+pub use helpers::send_reply;
+
+/// Handle a single request `body` sent by the client on `connection_id`,
+/// answering it through `notifier` with an ack once it has been applied or
+/// an error if it was rejected. Replies echo the client's request ID.
+/// Session tokens for rejoining are issued and checked with `signer`.
+pub fn handle_request(store: &dyn GameStore, notifier: &dyn Notifier, signer: &SessionSigner, connection_id: &str, body: Option<&str>) {
+    info!("{:?}", body.map(redact_body));
+
+    let (request_id, replies) = match parse_client_message(body) {
+        Ok(envelope) => {
+            let action_name = envelope.message.action();
+            let replies = match handle_message(store, signer, connection_id, envelope.request_id.as_deref(), envelope.message) {
+                Ok(replies) => replies,
+                Err(err) => vec![err.for_action(action_name).to_reply()],
+            };
+            (envelope.request_id, replies)
+        },
+        Err(err) => (err.request_id().map(|id| id.to_string()), vec![ActionError::from(err).to_reply()]),
+    };
+
+    for reply in &replies {
+        send_reply(notifier, connection_id, request_id.as_deref(), reply);
+    }
+}
+
+/// Handle the client on `connection_id` disconnecting by marking their
+/// player offline and forgetting the connection. There is nobody left to
+/// reply to, so failures are only logged.
+pub fn handle_disconnect(store: &dyn GameStore, connection_id: &str) {
+    match store.find_connection(connection_id) {
+        Ok(Some(entry)) => prune_connection(store, &entry.lobby_id, connection_id),
+        Ok(None) => info!("Connection {} was not in a game", connection_id),
+        Err(err) => error!("Error finding game for connection {}: {:?}", connection_id, err),
+    }
+}
+
+/// Mark the player on `connection_id` in `lobby_id` offline and forget the
+/// connection, for when it has closed or been found to be gone. Failures are
+/// only logged.
+pub fn prune_connection(store: &dyn GameStore, lobby_id: &str, connection_id: &str) {
+    let action = engine::Action::Disconnect { at: helpers::now() };
+    if let Err(err) = helpers::apply_action(store, lobby_id.to_string(), connection_id, None, action) {
+        error!("Error disconnecting {}: {}", connection_id, err);
+    }
+    if let Err(err) = store.unbind_connection(connection_id) {
+        error!("Error unbinding connection {}: {:?}", connection_id, err);
+    }
+}
+
+/// Apply `message`, returning the replies to send: an ack with the version of
+/// the game state it produced, followed by the caller's view of the game after
+/// a rejoin, whether by token or by joining again with their name and secret.
+/// A `state` request is answered with just that view.
+fn handle_message(store: &dyn GameStore, signer: &SessionSigner, connection_id: &str, request_id: Option<&str>,
+        message: ClientMessage) -> Result<Vec<ServerMessage>, ActionError> {
+    let action_name = message.action().to_string();
+    let ack = |state_version, token| ServerMessage::Ack(AckReply { action: action_name.clone(), state_version, token });
+    let request = request_id.map(|id| helpers::Request { id, action: &action_name });
+    let (code, action) = match message {
+        ClientMessage::Join { name, secret, code } => {
+            let (state_version, token, view) = handle_join(store, signer, connection_id, request, name, secret, code)?;
+            let mut replies = vec![ack(state_version, Some(token))];
+            replies.extend(view);
+            return Ok(replies);
+        },
+        ClientMessage::Rejoin { token } => {
+            let claims = signer.verify(&token)
+                .ok_or_else(|| ActionError::new(ErrorCode::InvalidToken, "Invalid session token"))?;
+            let action = engine::Action::Rejoin { player: claims.player_id.clone() };
+            let (state_version, game_state) = helpers::apply_action(store, claims.lobby_id, connection_id, request, action)?;
+            helpers::bind_connection(store, connection_id, &game_state, &claims.player_id);
+            return Ok(vec![ack(state_version, None), helpers::player_state(&game_state, connection_id)?]);
+        },
+        ClientMessage::Bodyguard { code, player } => (code, engine::Action::Bodyguard { player }),
+        ClientMessage::Cupid { code, players } => (code, engine::Action::Cupid { players }),
+        ClientMessage::Hunter { code, player } => (code, engine::Action::Hunter { player }),
+        ClientMessage::Lynch { code, player } => (code, engine::Action::Lynch { player }),
+        ClientMessage::Seer { code, player } => (code, engine::Action::Seer { player }),
+        ClientMessage::Sleep { code } => (code, engine::Action::Sleep {}),
+        ClientMessage::Start { code, werewolves, bodyguard, seer, lycan, tanner, witch, hunter, cupid, minion, masons } =>
+            (code, engine::Action::Start { werewolves, bodyguard, seer, lycan, tanner, witch, hunter, cupid, minion, masons }),
+        ClientMessage::State { code } => {
+            let game_state = helpers::get_state(store, helpers::lobby_for(store, connection_id, code)?)?;
+            return Ok(vec![helpers::player_state(&game_state, connection_id)?]);
+        },
+        ClientMessage::Werewolf { code, player } => (code, engine::Action::Werewolf { player }),
+        ClientMessage::Witch { code, heal, poison } => (code, engine::Action::Witch { heal: heal.unwrap_or(false), poison }),
+    };
+    let lobby_id = helpers::lobby_for(store, connection_id, code)?;
+    let (state_version, _) = helpers::apply_action(store, lobby_id, connection_id, request, action)?;
+    Ok(vec![ack(state_version, None)])
+}
+
+#[derive(Debug)]
+pub struct ActionError {
+    code: ErrorCode,
+    action: Option<String>,
+    details: String
+}
+
+impl ActionError {
+    pub fn new(code: ErrorCode, msg: &str) -> ActionError {
+        ActionError{code, action: None, details: msg.to_string()}
+    }
+
+    /// Record the action the failed request was for, if not already known.
+    fn for_action(mut self, action: &str) -> ActionError {
+        if self.action.is_none() {
+            self.action = Some(action.to_string());
+        }
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
+    }
+
+    /// The error as a reply to send back to the client.
+    pub fn to_reply(&self) -> ServerMessage {
+        ServerMessage::Error(ErrorReply {
+            code: self.code,
+            action: self.action.clone(),
+            message: self.details.clone(),
+        })
+    }
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.details)
+    }
+}
+
+impl Error for ActionError {
+    fn description(&self) -> &str {
+        &self.details
+    }
+}
+
+impl From<ProtocolError> for ActionError {
+    fn from(err: ProtocolError) -> Self {
+        let error = ActionError::new(err.code(), &err.to_string());
+        match err.action() {
+            Some(action) => error.for_action(action),
+            None => error,
+        }
+    }
+}
+
+impl From<engine::RuleError> for ActionError {
+    fn from(err: engine::RuleError) -> Self {
+        ActionError::new(err.code(), &err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::sync::{Mutex, Once};
+
+    use log::{Log, Metadata, Record};
+
+    use common::notifier::RecordingNotifier;
+    use common::{GameState, PhaseName, PlayerRole, PlayerTeam};
+    use common::store::{ConnectionEntry, MemoryStore, StoreError};
+
+    use super::*;
+
+    /// Keeps every log line written by any test, standing in for CloudWatch.
+    struct CapturingLogger {
+        lines: Mutex<Vec<String>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.lines.lock().unwrap().push(format!("{}", record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger { lines: Mutex::new(Vec::new()) };
+    static INIT_LOGGER: Once = Once::new();
+
+    fn captured_logs() -> Vec<String> {
+        INIT_LOGGER.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        LOGGER.lines.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn secrets_never_reach_the_logs() {
+        captured_logs();
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "first-secret".to_string(), 0).unwrap();
+        game_state.players.push(common::Player { secret: "legacy-secret".to_string(), ..game_state.players[0].clone() });
+        game_state.players[1].id = "p2".to_string();
+        game_state.players[1].name = "Bob".to_string();
+        store.create(&game_state).unwrap();
+
+        let bodies = [
+            r#"{"version": 2, "action": "join", "data": {"name": "Charles", "secret": "joining-secret", "code": "ABCD"}}"#,
+            r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "wrong-secret", "code": "ABCD"}}"#,
+            r#"{"version": 2, "action": "join", "data": {"name": "Bob", "secret": "legacy-secret", "code": "ABCD"}}"#,
+            r#"{"version": 2, "action": "join", "data": {"name": "Debbie", "secret": "unknown-lobby-secret", "code": "NONE"}}"#,
+            r#"{"version": 2, "action": "join", "data": {"name": "Emma", "secret": "extra-field-secret", "admin": true}}"#,
+            r#"{"version": 2, "action": "join", "data": {"name": "Fred", "secret": "broken-json-secret""#,
+            r#"{"version": 2, "action": "rejoin", "data": {"token": "forged-token"}}"#,
+        ];
+        for body in bodies.iter() {
+            handle_request(&store, &notifier, &signer(), "c2", Some(body));
+        }
+        info!("{:?}", store.load("ABCD").unwrap());
+
+        let logs = captured_logs().join("\n");
+        assert!(logs.contains(common::redact::REDACTED));
+        for secret in ["first-secret", "legacy-secret", "joining-secret", "wrong-secret", "unknown-lobby-secret",
+                "extra-field-secret", "broken-json-secret", "forged-token"].iter() {
+            assert!(!logs.contains(secret), "{} was logged", secret);
+        }
+    }
+
+    fn signer() -> SessionSigner {
+        SessionSigner::new(b"test key")
+    }
+
+    /// Requests that another client gets saved first.
+    type Race = Box<dyn FnOnce(&MemoryStore)>;
+
+    /// A store where the first `collisions` new games find their lobby code
+    /// taken, and where the next save loses to a `race` saved just before it.
+    struct FlakyStore {
+        store: MemoryStore,
+        collisions: Cell<u32>,
+        race: RefCell<Option<Race>>,
+    }
+
+    impl FlakyStore {
+        fn new() -> FlakyStore {
+            FlakyStore { store: MemoryStore::new(), collisions: Cell::new(0), race: RefCell::new(None) }
+        }
+    }
+
+    impl GameStore for FlakyStore {
+        fn create(&self, game_state: &GameState) -> Result<(), StoreError> {
+            if self.collisions.get() > 0 {
+                self.collisions.set(self.collisions.get() - 1);
+                return Err(StoreError::AlreadyExists);
+            }
+            self.store.create(game_state)
+        }
+
+        fn load(&self, lobby_id: &str) -> Result<GameState, StoreError> {
+            self.store.load(lobby_id)
+        }
+
+        fn save(&self, game_state: GameState) -> Result<GameState, StoreError> {
+            let race = self.race.borrow_mut().take();
+            if let Some(race) = race {
+                race(&self.store);
+                return Err(StoreError::Conflict);
+            }
+            self.store.save(game_state)
+        }
+
+        fn bind_connection(&self, entry: &ConnectionEntry) -> Result<(), StoreError> {
+            self.store.bind_connection(entry)
+        }
+
+        fn find_connection(&self, connection_id: &str) -> Result<Option<ConnectionEntry>, StoreError> {
+            self.store.find_connection(connection_id)
+        }
+
+        fn unbind_connection(&self, connection_id: &str) -> Result<(), StoreError> {
+            self.store.unbind_connection(connection_id)
+        }
+    }
+
+    /// A game on its first night in lobby `ABCD`, with a player on `c<n>`
+    /// for each of `roles`, numbered from 1.
+    fn night(roles: &[PlayerRole]) -> GameState {
+        let secret = common::secret::hash_secret("s");
+        let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Player 1".to_string(), secret.clone(), 0).unwrap();
+        for n in 2..=roles.len() {
+            let join = engine::Action::Join { name: format!("Player {}", n), secret: secret.clone() };
+            game_state = engine::apply(game_state, &format!("c{}", n), join).unwrap();
+        }
+        for (player, role) in game_state.players.iter_mut().zip(roles) {
+            player.attributes.role = role.clone();
+            player.attributes.team = match role {
+                PlayerRole::Mod => PlayerTeam::Mod,
+                PlayerRole::Werewolf => PlayerTeam::Evil,
+                _ => PlayerTeam::Good,
+            };
+        }
+        game_state.phase.name = PhaseName::Werewolf;
+        game_state
+    }
+
+    fn eat(player: &str) -> String {
+        format!(r#"{{"version": 2, "action": "werewolf", "data": {{"code": "ABCD", "player": "{}"}}}}"#, player)
+    }
+
+    #[test]
+    fn conflicting_saves_are_applied_again() {
+        let roles = [PlayerRole::Mod, PlayerRole::Werewolf, PlayerRole::Werewolf, PlayerRole::Werewolf, PlayerRole::Villager,
+            PlayerRole::Villager, PlayerRole::Villager, PlayerRole::Villager];
+        let store = FlakyStore::new();
+        store.create(&night(&roles)).unwrap();
+        *store.race.borrow_mut() = Some(Box::new(|store: &MemoryStore| {
+            handle_request(store, &RecordingNotifier::new(), &signer(), "c3", Some(&eat("p6")));
+        }));
+
+        let notifier = RecordingNotifier::new();
+        handle_request(&store, &notifier, &signer(), "c2", Some(&eat("p5")));
+        assert!(notifier.messages_for("c2")[0]["ack"].is_object());
+        let game_state = store.load("ABCD").unwrap();
+        assert_eq!(game_state.phase.name, PhaseName::Werewolf);
+        assert_eq!(game_state.phase.data["p2"], "p5");
+        assert_eq!(game_state.phase.data["p3"], "p6");
+    }
+
+    #[test]
+    fn actions_a_conflicting_save_made_invalid_are_rejected() {
+        let roles = [PlayerRole::Mod, PlayerRole::Werewolf, PlayerRole::Werewolf, PlayerRole::Villager, PlayerRole::Villager,
+            PlayerRole::Villager, PlayerRole::Villager, PlayerRole::Villager];
+        let store = FlakyStore::new();
+        store.create(&night(&roles)).unwrap();
+        *store.race.borrow_mut() = Some(Box::new(|store: &MemoryStore| {
+            handle_request(store, &RecordingNotifier::new(), &signer(), "c2", Some(&eat("p4")));
+            handle_request(store, &RecordingNotifier::new(), &signer(), "c3", Some(&eat("p4")));
+        }));
+
+        let notifier = RecordingNotifier::new();
+        handle_request(&store, &notifier, &signer(), "c2", Some(&eat("p5")));
+        let replies = notifier.messages_for("c2");
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0]["error"]["code"], "NOT_YOUR_TURN");
+        let game_state = store.load("ABCD").unwrap();
+        assert_eq!(game_state.phase.name, PhaseName::Day);
+        assert!(game_state.players[4].attributes.alive);
+    }
+
+    #[test]
+    fn new_games_retry_taken_codes() {
+        let notifier = RecordingNotifier::new();
+        let join = r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "s"}}"#;
+        let store = FlakyStore::new();
+        store.collisions.set(2);
+        handle_request(&store, &notifier, &signer(), "c1", Some(join));
+        assert!(notifier.messages_for("c1")[0]["ack"].is_object());
+        assert!(store.find_connection("c1").unwrap().is_some());
+
+        let store = FlakyStore::new();
+        store.collisions.set(helpers::MAX_ATTEMPTS);
+        handle_request(&store, &notifier, &signer(), "c2", Some(join));
+        assert_eq!(notifier.messages_for("c2")[0]["error"]["code"], "STATE_CONFLICT");
+    }
+
+    #[test]
+    fn replies_echo_request_ids() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "request_id": "r1", "action": "join", "data": {"name": "Adam", "secret": "s"}}"#));
+        let ack = &notifier.messages_for("c1")[0];
+        assert_eq!(ack["request_id"], "r1");
+        assert_eq!(ack["ack"]["action"], "join");
+        assert!(ack["ack"]["state_version"].is_u64());
+
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "request_id": "r2", "action": "sleep", "data": {"code": "NONE"}}"#));
+        let error = &notifier.messages_for("c1")[1];
+        assert_eq!(error["request_id"], "r2");
+        assert_eq!(error["error"]["code"], "LOBBY_NOT_FOUND");
+        assert_eq!(error["error"]["action"], "sleep");
+    }
+
+    #[test]
+    fn resent_requests_are_applied_once() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        store.create(&game_state).unwrap();
+
+        let join = r#"{"version": 2, "request_id": "r1", "action": "join", "data": {"name": "Bob", "secret": "t", "code": "ABCD"}}"#;
+        handle_request(&store, &notifier, &signer(), "c2", Some(join));
+        handle_request(&store, &notifier, &signer(), "c2", Some(join));
+
+        let replies = notifier.messages_for("c2");
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0], replies[1]);
+        let game_state = store.load("ABCD").unwrap();
+        assert_eq!(game_state.players.len(), 2);
+        assert_eq!(replies[1]["ack"]["state_version"], game_state.version);
+
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "request_id": "r1", "action": "sleep", "data": {}}"#));
+        let reply = &notifier.messages_for("c2")[2];
+        assert_eq!(reply["request_id"], "r1");
+        assert_eq!(reply["error"]["code"], "INVALID_REQUEST");
+        assert_eq!(store.load("ABCD").unwrap().version, game_state.version);
+    }
+
+    #[test]
+    fn requests_resent_after_reconnecting_are_applied_once() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let new_game = r#"{"version": 2, "request_id": "r1", "action": "join", "data": {"name": "Adam", "secret": "s"}}"#;
+        handle_request(&store, &notifier, &signer(), "c1", Some(new_game));
+        handle_request(&store, &notifier, &signer(), "c1", Some(new_game));
+        assert_eq!(notifier.messages_for("c1")[0]["ack"]["state_version"], notifier.messages_for("c1")[1]["ack"]["state_version"]);
+        let lobby_id = store.find_connection("c1").unwrap().unwrap().lobby_id;
+
+        // Another player may tag their own new game with the same request ID.
+        let other_game = r#"{"version": 2, "request_id": "r1", "action": "join", "data": {"name": "Adam", "secret": "other"}}"#;
+        handle_request(&store, &notifier, &signer(), "c9", Some(other_game));
+        assert_ne!(store.find_connection("c9").unwrap().unwrap().lobby_id, lobby_id);
+        assert_eq!(store.find_connection("c1").unwrap().unwrap().lobby_id, lobby_id);
+
+        let join = format!(r#"{{"version": 2, "request_id": "r1", "action": "join", "data": {{"name": "Bob", "secret": "t", "code": "{}"}}}}"#, lobby_id);
+        handle_request(&store, &notifier, &signer(), "c3", Some(&join));
+        handle_request(&store, &notifier, &signer(), "c4", Some(&join));
+        assert_eq!(notifier.messages_for("c3")[0]["ack"]["state_version"], notifier.messages_for("c4")[0]["ack"]["state_version"]);
+
+        let start = r#"{"version": 2, "request_id": "r2", "action": "start", "data": {"werewolves": 1, "seer": false}}"#;
+        handle_request(&store, &notifier, &signer(), "c1", Some(start));
+        let token = notifier.messages_for("c1")[0]["ack"]["token"].as_str().unwrap().to_string();
+        handle_request(&store, &notifier, &signer(), "c5", Some(&format!(r#"{{"version": 2, "action": "rejoin", "data": {{"token": "{}"}}}}"#, token)));
+        handle_request(&store, &notifier, &signer(), "c5", Some(start));
+        assert_eq!(notifier.messages_for("c1")[2]["ack"]["state_version"], notifier.messages_for("c5")[2]["ack"]["state_version"]);
+
+        let game_state = store.load(&lobby_id).unwrap();
+        assert_eq!(game_state.players.len(), 2);
+        assert_eq!(game_state.players.iter().find(|p| p.name == "Adam").unwrap().connection_id, "c5");
+        assert_eq!(game_state.players.iter().find(|p| p.name == "Bob").unwrap().connection_id, "c4");
+    }
+
+    #[test]
+    fn legacy_players_are_migrated_on_load() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        game_state.players[0].id = "c1".to_string();
+        game_state.players[0].connection_id = "".to_string();
+        game_state.players[0].secret = "plain".to_string();
+        store.create(&game_state).unwrap();
+
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "plain", "code": "ABCD"}}"#));
+
+        assert!(notifier.messages_for("c2")[0].get("ack").is_some());
+        let player = &store.load("ABCD").unwrap().players[0];
+        assert_eq!(player.id, "Adam");
+        assert_eq!(player.connection_id, "c2");
+        assert!(common::secret::verify_secret(&player.secret, "plain"));
+
+        let version = store.load("ABCD").unwrap().version;
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "state", "data": {}}"#));
+        assert_eq!(store.load("ABCD").unwrap().version, version);
+    }
+
+    #[test]
+    fn session_tokens_rebind_connections() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        store.create(&game_state).unwrap();
+
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "join", "data": {"name": "Bob", "secret": "t", "code": "ABCD"}}"#));
+        let token = notifier.messages_for("c2")[0]["ack"]["token"].as_str().unwrap().to_string();
+
+        let rejoin = format!(r#"{{"version": 2, "action": "rejoin", "data": {{"token": "{}"}}}}"#, token);
+        handle_request(&store, &notifier, &signer(), "c3", Some(&rejoin));
+        let replies = notifier.messages_for("c3");
+        assert!(replies[0].get("ack").is_some());
+        assert_eq!(replies[1]["game_state"]["lobbyId"], "ABCD");
+        let game_state = store.load("ABCD").unwrap();
+        assert!(game_state.players.iter().any(|p| p.name == "Bob" && p.id == "p2" && p.connection_id == "c3"));
+
+        handle_request(&store, &notifier, &SessionSigner::new(b"other key"), "c4", Some(&rejoin));
+        assert_eq!(notifier.messages_for("c4")[0]["error"]["code"], "INVALID_TOKEN");
+    }
+
+    #[test]
+    fn rejoining_by_name_sends_the_callers_view() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), common::secret::hash_secret("s"), 0).unwrap();
+        store.create(&game_state).unwrap();
+
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "join", "data": {"name": "Bob", "secret": "t", "code": "ABCD"}}"#));
+        assert_eq!(notifier.messages_for("c2").len(), 1);
+
+        handle_request(&store, &notifier, &signer(), "c3", Some(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "s", "code": "ABCD"}}"#));
+        let replies = notifier.messages_for("c3");
+        assert_eq!(replies.len(), 2);
+        assert!(replies[0]["ack"]["token"].is_string());
+        assert_eq!(replies[1]["game_state"]["lobbyId"], "ABCD");
+        assert_eq!(replies[1]["game_state"]["players"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn wrong_secrets_are_rejected() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        store.create(&game_state).unwrap();
+
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "wrong", "code": "ABCD"}}"#));
+        assert_eq!(notifier.messages_for("c2")[0]["error"]["code"], "INVALID_PLAYER");
+        assert_eq!(store.load("ABCD").unwrap().players[0].connection_id, "c1");
+    }
+
+    #[test]
+    fn actions_target_players_by_id() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        for (connection_id, name) in [("c2", "Bob"), ("c3", "Bob "), ("c4", "Charles")].iter() {
+            game_state = engine::apply(game_state, connection_id, engine::Action::Join { name: name.to_string(), secret: "s".to_string() }).unwrap();
+        }
+        store.create(&game_state).unwrap();
+
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "action": "start", "data": {"code": "ABCD", "werewolves": 1, "seer": false}}"#));
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "action": "lynch", "data": {"code": "ABCD", "player": "Bob"}}"#));
+        assert_eq!(notifier.messages_for("c1")[1]["error"]["code"], "INVALID_TARGET");
+
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "action": "lynch", "data": {"code": "ABCD", "player": "p3"}}"#));
+        assert!(notifier.messages_for("c1")[2].get("ack").is_some());
+        let game_state = store.load("ABCD").unwrap();
+        let dead: Vec<&str> = game_state.players.iter().filter(|p| !p.attributes.alive).map(|p| p.name.as_str()).collect();
+        assert_eq!(dead, vec!["Bob "]);
+    }
+
+    #[test]
+    fn disconnecting_marks_players_offline() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        store.create(&game_state).unwrap();
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "join", "data": {"name": "Bob", "secret": "t", "code": "ABCD"}}"#));
+        let token = notifier.messages_for("c2")[0]["ack"]["token"].as_str().unwrap().to_string();
+        let game_state = store.load("ABCD").unwrap();
+        assert_eq!(game_state.players[0].last_seen, 0);
+        assert!(game_state.players[1].last_seen > 0);
+
+        handle_disconnect(&store, "c2");
+        handle_disconnect(&store, "unknown");
+        let bob = store.load("ABCD").unwrap().players.into_iter().find(|p| p.name == "Bob").unwrap();
+        assert!(!bob.online);
+        assert!(bob.last_seen > 0);
+
+        let rejoin = format!(r#"{{"version": 2, "action": "rejoin", "data": {{"token": "{}"}}}}"#, token);
+        handle_request(&store, &notifier, &signer(), "c3", Some(&rejoin));
+        let bob = store.load("ABCD").unwrap().players.into_iter().find(|p| p.name == "Bob").unwrap();
+        assert!(bob.online);
+
+        handle_request(&store, &notifier, &signer(), "c4", Some(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "s", "code": "ABCD"}}"#));
+        assert!(store.load("ABCD").unwrap().players[0].last_seen > 0);
+    }
+
+    #[test]
+    fn connections_find_their_lobby() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "s"}}"#));
+        let lobby_id = store.find_connection("c1").unwrap().unwrap().lobby_id;
+
+        handle_request(&store, &notifier, &signer(), "c1", Some(r#"{"version": 2, "action": "start", "data": {"werewolves": 1}}"#));
+        assert_eq!(notifier.messages_for("c1")[1]["error"]["code"], "TOO_MANY_ROLES");
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "action": "sleep", "data": {}}"#));
+        assert_eq!(notifier.messages_for("c2")[0]["error"]["code"], "LOBBY_NOT_FOUND");
+
+        handle_disconnect(&store, "c1");
+        assert_eq!(store.find_connection("c1").unwrap(), None);
+        assert!(!store.load(&lobby_id).unwrap().players[0].online);
+    }
+
+    #[test]
+    fn gone_connections_are_pruned() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), "s".to_string(), 0).unwrap();
+        game_state = engine::apply(game_state, "c2", engine::Action::Join { name: "Bob".to_string(), secret: "t".to_string() }).unwrap();
+        store.create(&game_state).unwrap();
+
+        notifier.disconnect("c2");
+        let gone = common::broadcast::broadcast_state(&notifier, &game_state);
+        assert_eq!(gone, vec!["c2".to_string()]);
+        for connection_id in gone {
+            prune_connection(&store, "ABCD", &connection_id);
+        }
+
+        let game_state = store.load("ABCD").unwrap();
+        assert!(game_state.players[0].online);
+        assert!(!game_state.players[1].online);
+        assert!(common::broadcast::broadcast_state(&notifier, &game_state).is_empty());
+    }
+
+    #[test]
+    fn state_requests_return_the_callers_view() {
+        let store = MemoryStore::new();
+        let notifier = RecordingNotifier::new();
+        let secret = common::secret::hash_secret("s");
+        let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Adam".to_string(), secret.clone(), 0).unwrap();
+        for (connection_id, name) in [("c2", "Bob"), ("c3", "Charles"), ("c4", "Debbie")].iter() {
+            game_state = engine::apply(game_state, connection_id, engine::Action::Join { name: name.to_string(), secret: secret.clone() }).unwrap();
+        }
+        game_state = engine::apply(game_state, "c1", engine::Action::Start { werewolves: 1, bodyguard: None, seer: None, lycan: None, tanner: None, witch: None, hunter: None, cupid: None, minion: None, masons: None }).unwrap();
+        store.create(&game_state).unwrap();
+
+        handle_request(&store, &notifier, &signer(), "c2", Some(r#"{"version": 2, "request_id": "r1", "action": "state", "data": {"code": "ABCD"}}"#));
+        let reply = &notifier.messages_for("c2")[0];
+        assert_eq!(reply["request_id"], "r1");
+        let players = reply["game_state"]["players"].as_array().unwrap();
+        assert_eq!(players.len(), 4);
+        for player in players {
+            assert!(player.get("secret").is_none());
+            if player["id"] != "p2" && player["attributes"]["role"] != "Mod" {
+                assert_eq!(player["attributes"]["role"], "Unknown");
+            }
+        }
+        assert_eq!(store.load("ABCD").unwrap().version, game_state.version);
+
+        handle_request(&store, &notifier, &signer(), "c5", Some(r#"{"version": 2, "action": "state", "data": {"code": "ABCD"}}"#));
+        assert_eq!(notifier.messages_for("c5")[0]["error"]["code"], "PLAYER_NOT_FOUND");
+    }
+}
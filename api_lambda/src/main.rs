@@ -1,58 +1,23 @@
 extern crate lambda;
 #[macro_use]
-extern crate serde_derive;
-#[macro_use]
 extern crate log;
 extern crate simple_logger;
-extern crate rand;
 extern crate tokio;
-extern crate rusoto_core;
-extern crate rusoto_dynamodb;
-extern crate serde_dynamodb;
 
 use lambda::{handler_fn, Context};
 
-use std::fmt;
-use std::error::Error;
 use std::collections::HashMap;
 
 use aws_lambda_events::event::apigw::ApiGatewayProxyResponse;
 
-use serde_json::{Value, Map};
 use simple_logger::SimpleLogger;
 use log::LevelFilter;
 
-mod bodyguard;
-use bodyguard::handle_bodyguard;
-
-mod join;
-use join::handle_join;
-
-mod lynch;
-use lynch::handle_lynch;
-
-mod seer;
-use seer::handle_seer;
-
-mod sleep;
-use sleep::handle_sleep;
-
-mod start;
-use start::handle_start;
-
-mod werewolf;
-use werewolf::handle_werewolf;
+use common::ErrorCode;
+use common::notifier::ApiGatewayNotifier;
 
 mod helpers;
 
-
-#[derive(Deserialize, Serialize, Clone)]
-struct RouteEvent {
-    action: String,
-    #[serde(skip)]
-    data: Map<String, Value>,
-}
-
 type LambdaError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 #[tokio::main]
@@ -63,60 +28,58 @@ async fn main() -> Result<(), LambdaError> {
     Ok(())
 }
 
-async fn handler(e: common::ApiGatewayWebsocketProxyRequest, c: Context) -> Result<ApiGatewayProxyResponse, LambdaError> {
-    let body = e.body.clone().unwrap();
-    info!("{:?}", body);
-    let event: RouteEvent = serde_json::from_str(&body).unwrap();
-    
-    let error = match &event.action as &str {
-        "bodyguard" => handle_bodyguard(e.clone()),
-        "join" => handle_join(e.clone(), c),
-        "lynch" => handle_lynch(e.clone()),
-        "seer" => handle_seer(e.clone()),
-        "sleep" => handle_sleep(e.clone()),
-        "start" => handle_start(e.clone()),
-        "werewolf" => handle_werewolf(e.clone()),
-        _ => handle_unknown(event.action),
+async fn handler(e: common::ApiGatewayWebsocketProxyRequest, _c: Context) -> Result<ApiGatewayProxyResponse, LambdaError> {
+    let connection_id = match &e.request_context.connection_id {
+        Some(connection_id) => connection_id.clone(),
+        None => {
+            error!("No connection ID on request context");
+            return Ok(response(400));
+        },
     };
 
-    if let Err(action_error) = error {
-        helpers::send_error(format!("Unknown action \"{}\"!", action_error),
-            e.clone().request_context.connection_id.unwrap(),
-            helpers::endpoint(&e.request_context));
+    match e.request_context.route_key.as_deref() {
+        Some("$connect") => {
+            info!("Connection {} opened", connection_id);
+            return Ok(response(200));
+        },
+        Some("$disconnect") => {
+            helpers::STORE.with(|store| match store {
+                Some(store) => api::handle_disconnect(store, &connection_id),
+                None => error!("No tableName or connectionsTableName in environment"),
+            });
+            return Ok(response(200));
+        },
+        _ => {},
     }
 
-    Ok(ApiGatewayProxyResponse {
-        status_code: 200,
+    let notifier = match helpers::endpoint(&e.request_context) {
+        Some(endpoint) => ApiGatewayNotifier::new(endpoint),
+        None => {
+            error!("No domain or stage on request context");
+            return Ok(response(400));
+        },
+    };
+
+    helpers::STORE.with(|store| helpers::SIGNER.with(|signer| {
+        match (store, signer) {
+            (Some(store), Some(signer)) => api::handle_request(store, &notifier, signer, &connection_id, e.body.as_deref()),
+            _ => {
+                error!("No tableName, connectionsTableName or sessionKey in environment");
+                let err = api::ActionError::new(ErrorCode::InternalError, "Server misconfigured, please try again later");
+                api::send_reply(&notifier, &connection_id, None, &err.to_reply());
+            },
+        }
+    }));
+
+    Ok(response(200))
+}
+
+fn response(status_code: i64) -> ApiGatewayProxyResponse {
+    ApiGatewayProxyResponse {
+        status_code,
         headers: HashMap::new(),
         multi_value_headers: HashMap::new(),
         body: None,
         is_base64_encoded: None,
-    })
-}
-
-fn handle_unknown(action: String) -> Result<(), ActionError> {
-    Err(ActionError::new(&format!("Unknown action \"{}\"!", action)))
-}
-
-#[derive(Debug)]
-pub struct ActionError {
-    details: String
-}
-
-impl ActionError {
-    fn new(msg: &str) -> ActionError {
-        ActionError{details: msg.to_string()}
-    }
-}
-
-impl fmt::Display for ActionError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"{}",self.details)
-    }
-}
-
-impl Error for ActionError {
-    fn description(&self) -> &str {
-        &self.details
     }
 }
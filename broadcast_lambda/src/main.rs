@@ -2,30 +2,24 @@ extern crate lambda;
 extern crate serde_derive;
 extern crate simple_logger;
 
-use futures::future::join_all;
-use futures::executor::block_on;
-use bytes::Bytes;
-
 use std::env;
 use std::collections::HashMap;
 
 use aws_lambda_events::event::apigw::ApiGatewayProxyResponse;
-use rusoto_apigatewaymanagementapi::{
-    ApiGatewayManagementApi, ApiGatewayManagementApiClient, PostToConnectionRequest, PostToConnectionError,
-};
-use rusoto_core::{Region, RusotoError};
-use serde_json::{json, Value};
 use lambda::{lambda, Context};
 
 use common::GameState;
+use common::notifier::{Notifier, ApiGatewayNotifier};
+use common::store::{DynamoStore, GameStore};
 
 type LambdaError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 thread_local!(
-    pub static API_GW: ApiGatewayManagementApiClient = ApiGatewayManagementApiClient::new(Region::Custom {
-        name: Region::EuWest2.name().into(),
-        endpoint: endpoint(),
-    });
+    pub static NOTIFIER: ApiGatewayNotifier = ApiGatewayNotifier::new(endpoint());
+    pub static STORE: Option<DynamoStore> = match (env::var("tableName"), env::var("connectionsTableName")) {
+        (Ok(table_name), Ok(connections_table_name)) => Some(DynamoStore::new(table_name, connections_table_name)),
+        _ => None,
+    };
 );
 
 #[lambda]
@@ -33,9 +27,14 @@ thread_local!(
 async fn main(e: common::DDBStreamEvent, _c: Context) -> Result<ApiGatewayProxyResponse, LambdaError> {
     match e.records {
         Some(records) => {
-            for record in &records {
-                process_record(record).await;
-            }
+            NOTIFIER.with(|notifier| STORE.with(|store| {
+                if store.is_none() {
+                    log::error!("No tableName or connectionsTableName in environment, gone connections will not be pruned");
+                }
+                for record in &records {
+                    process_record(notifier, store.as_ref().map(|s| s as &dyn GameStore), record);
+                }
+            }));
         },
         None => log::warn!("No records in event, empty execution..."),
     }
@@ -49,7 +48,7 @@ async fn main(e: common::DDBStreamEvent, _c: Context) -> Result<ApiGatewayProxyR
     })
 }
 
-async fn process_record(record: &common::DDBRecord) {
+fn process_record(notifier: &dyn Notifier, store: Option<&dyn GameStore>, record: &common::DDBRecord) {
     match &record.dynamodb {
         Some(stream_record) => {
             match &stream_record.stream_view_type {
@@ -59,20 +58,12 @@ async fn process_record(record: &common::DDBRecord) {
                             match &stream_record.new_image {
                                 Some(new_image) => {
                                     let game_state: GameState = serde_dynamodb::from_hashmap(new_image.clone()).unwrap();
-                                    let players = game_state.players.clone();
-                                    let broadcasts = players.into_iter().map(|p| {
-                                        let filtered_state = filter_state(&p, game_state.clone());
-                                        let value_state = serde_json::to_value(&filtered_state).unwrap();
-                                        let mut map_state: HashMap<String, Value> = serde_json::from_value(value_state).unwrap();
-                                        let lobby_id = map_state.remove("lobby_id").unwrap();
-                                        map_state.insert("lobbyId".to_string(), lobby_id);
-                                        map_state.remove("ttl");
-                                        broadcast(p, map_state)
-                                    }).collect::<Vec<_>>();
-                                    let results = join_all(broadcasts).await;
-                                    for r in results.into_iter() {
-                                        if let Err(err) = r {
-                                            log::error!("Error broadcasting: {:?}", err);
+                                    // Players on gone connections are marked offline, which saves
+                                    // the game and broadcasts again without them.
+                                    let gone = common::broadcast::broadcast_state(notifier, &game_state);
+                                    if let Some(store) = store {
+                                        for connection_id in gone {
+                                            api::prune_connection(store, &game_state.lobby_id, &connection_id);
                                         }
                                     }
                                 },
@@ -89,53 +80,6 @@ async fn process_record(record: &common::DDBRecord) {
     }
 }
 
-async fn broadcast(player: common::Player, game_state: HashMap<String, Value>) -> Result<(), RusotoError<PostToConnectionError>> {
-    API_GW.with(|api_gw| {
-        block_on(api_gw.post_to_connection(PostToConnectionRequest {
-            connection_id: player.id.clone(),
-            data: Bytes::from(json!({ "game_state": game_state }).to_string()),
-        }))
-    })
-}
-
-fn filter_state(player: &common::Player, game_state: common::GameState) -> common::GameState {
-    let mut new_state = game_state.clone();
-    if game_state.phase.name == common::PhaseName::Werewolf && 
-        !vec![common::PlayerRole::Mod, common::PlayerRole::Werewolf].contains(&player.attributes.role) {
-            new_state.phase.data = HashMap::new();
-    }
-    if game_state.phase.name == common::PhaseName::Bodyguard && player.attributes.role == common::PlayerRole::Bodyguard {
-        let mut phase_data = HashMap::new();
-        phase_data.insert("last_guarded".to_string(), game_state.internal_state.get("last_guarded").unwrap_or(&"".to_string()).clone());
-        new_state.phase.data = phase_data;
-    }
-    new_state.internal_state = HashMap::new();
-    new_state.players = new_state.players.into_iter().map(|p| {
-        let mut new_attributes = p.attributes.clone();
-        let mut new_player = p.clone();
-        new_player.secret = "".to_string();
-        new_player.id = "".to_string();
-        if game_state.phase.name != common::PhaseName::End {
-            if p.name != player.name && new_attributes.alive && new_attributes.role != common::PlayerRole::Mod {
-                if !new_attributes.visible_to.contains(&format!("{:?}", player.attributes.role)) {
-                    new_attributes.role = common::PlayerRole::Unknown;
-                    new_attributes.team = common::PlayerTeam::Unknown;
-                }
-                else if player.attributes.role == common::PlayerRole::Seer {
-                    new_attributes.role = common::PlayerRole::Unknown;
-                    if p.attributes.role == common::PlayerRole::Lycan {
-                        new_attributes.team = common::PlayerTeam::Evil;
-                    }
-                }
-            }
-            new_attributes.visible_to = vec![];
-            new_player.attributes = new_attributes;
-        }
-        new_player
-    }).collect();
-    new_state
-}
-
 fn endpoint() -> String {
     let domain_name = env::var("apiUrl").unwrap();
     domain_name.replace("wss://", "https://")
@@ -0,0 +1,32 @@
+use serde_json::Value;
+
+use crate::{view_for, GameState, Player};
+use crate::notifier::{Notifier, NotifyError};
+use crate::protocol::ServerMessage;
+
+/// Send every connected player in `game_state` their own filtered view of it,
+/// returning the IDs of any connections that turned out to be gone.
+pub fn broadcast_state(notifier: &dyn Notifier, game_state: &GameState) -> Vec<String> {
+    let mut gone = Vec::new();
+    for p in game_state.players.iter().filter(|p| p.online && !p.connection_id.is_empty()) {
+        match notifier.send(&p.connection_id, &state_message(p, game_state)) {
+            Ok(()) => {},
+            Err(NotifyError::Gone) => {
+                warn!("Connection {} is gone", p.connection_id);
+                gone.push(p.connection_id.clone());
+            },
+            Err(err) => error!("Error broadcasting: {:?}", err),
+        }
+    }
+    gone
+}
+
+/// The `game_state` message sent to `player`, containing only what they are allowed to see.
+pub fn state_message(player: &Player, game_state: &GameState) -> Value {
+    player_state(player, game_state).to_value()
+}
+
+/// `player`'s view of `game_state`, as a message that can also be sent as a reply.
+pub fn player_state(player: &Player, game_state: &GameState) -> ServerMessage {
+    ServerMessage::GameState(view_for(game_state, player))
+}
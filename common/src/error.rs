@@ -0,0 +1,20 @@
+/// Machine readable reason a request was rejected, for clients to switch on
+/// rather than matching against error messages.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidRequest,
+    UnsupportedVersion,
+    UnknownAction,
+    LobbyNotFound,
+    PlayerNotFound,
+    InvalidPlayer,
+    InvalidToken,
+    GameInProgress,
+    TooManyRoles,
+    NotYourTurn,
+    WrongRole,
+    InvalidTarget,
+    StateConflict,
+    InternalError,
+}
@@ -0,0 +1,17 @@
+use crate::GameState;
+
+/// Give players saved before players had permanent IDs, when `id` held their
+/// connection ID, an ID of their own, returning whether anything changed.
+/// Their name becomes their ID, so werewolf votes and `last_guarded`, which
+/// were kept by name, still refer to them.
+pub fn migrate_player_ids(game_state: &mut GameState) -> bool {
+    let mut migrated = false;
+    for player in game_state.players.iter_mut() {
+        if player.connection_id.is_empty() && player.id != player.name {
+            player.connection_id = player.id.clone();
+            player.id = player.name.clone();
+            migrated = true;
+        }
+    }
+    migrated
+}
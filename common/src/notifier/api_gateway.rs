@@ -0,0 +1,40 @@
+use bytes::Bytes;
+use futures::executor::block_on;
+use rusoto_apigatewaymanagementapi::{
+    ApiGatewayManagementApi, ApiGatewayManagementApiClient, PostToConnectionRequest, PostToConnectionError,
+};
+use rusoto_core::{Region, RusotoError};
+use serde_json::Value;
+
+use super::{Notifier, NotifyError};
+
+/// A `Notifier` that posts to connections through the API Gateway
+/// management API at `endpoint`.
+pub struct ApiGatewayNotifier {
+    client: ApiGatewayManagementApiClient,
+}
+
+impl ApiGatewayNotifier {
+    pub fn new(endpoint: String) -> ApiGatewayNotifier {
+        ApiGatewayNotifier {
+            client: ApiGatewayManagementApiClient::new(Region::Custom {
+                name: Region::default().name().into(),
+                endpoint,
+            }),
+        }
+    }
+}
+
+impl Notifier for ApiGatewayNotifier {
+    fn send(&self, connection_id: &str, message: &Value) -> Result<(), NotifyError> {
+        let result = block_on(self.client.post_to_connection(PostToConnectionRequest {
+            connection_id: connection_id.to_string(),
+            data: Bytes::from(message.to_string()),
+        }));
+        match result {
+            Ok(_) => Ok(()),
+            Err(RusotoError::Service(PostToConnectionError::Gone(_))) => Err(NotifyError::Gone),
+            Err(err) => Err(NotifyError::Failed(format!("{:?}", err))),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt;
+use std::error::Error;
+
+use serde_json::Value;
+
+mod recording;
+pub use recording::RecordingNotifier;
+
+#[cfg(feature = "aws")]
+mod api_gateway;
+#[cfg(feature = "aws")]
+pub use api_gateway::ApiGatewayNotifier;
+
+/// Delivery of outbound messages to client connections.
+pub trait Notifier {
+    /// Send `message` to the given connection, failing with `Gone` if the
+    /// client has since disconnected.
+    fn send(&self, connection_id: &str, message: &Value) -> Result<(), NotifyError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotifyError {
+    Gone,
+    Failed(String),
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotifyError::Gone => write!(f, "Connection is gone"),
+            NotifyError::Failed(details) => write!(f, "Error sending message: {}", details),
+        }
+    }
+}
+
+impl Error for NotifyError {}
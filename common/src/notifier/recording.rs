@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use super::{Notifier, NotifyError};
+
+/// A `Notifier` that keeps every message it is asked to send, so callers can
+/// inspect what each connection would have received.
+#[derive(Default)]
+pub struct RecordingNotifier {
+    sent: Mutex<Vec<(String, Value)>>,
+    gone: Mutex<HashSet<String>>,
+}
+
+impl RecordingNotifier {
+    pub fn new() -> RecordingNotifier {
+        RecordingNotifier::default()
+    }
+
+    /// Treat the connection as disconnected, so sends to it fail with `Gone`.
+    pub fn disconnect(&self, connection_id: &str) {
+        self.gone.lock().unwrap().insert(connection_id.to_string());
+    }
+
+    /// Every message delivered so far, in order, with its connection ID.
+    pub fn messages(&self) -> Vec<(String, Value)> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// The messages delivered so far to a single connection, in order.
+    pub fn messages_for(&self, connection_id: &str) -> Vec<Value> {
+        self.sent.lock().unwrap().iter()
+            .filter(|(id, _)| id == connection_id)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+}
+
+impl Notifier for RecordingNotifier {
+    fn send(&self, connection_id: &str, message: &Value) -> Result<(), NotifyError> {
+        if self.gone.lock().unwrap().contains(connection_id) {
+            return Err(NotifyError::Gone);
+        }
+        self.sent.lock().unwrap().push((connection_id.to_string(), message.clone()));
+        Ok(())
+    }
+}
@@ -0,0 +1,506 @@
+use std::fmt;
+use std::error::Error;
+
+use serde_json::Value;
+
+use crate::{ErrorCode, PlayerView};
+
+/// Version of the wire protocol described by `ClientMessage` and
+/// `ServerMessage`. Bump it whenever a change would break existing clients.
+///
+/// Version 2 targets players by their `id` rather than their name.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Every action a client can send, in the order they appear in `ClientMessage`.
+pub const ACTIONS: [&str; 12] = ["join", "rejoin", "bodyguard", "cupid", "hunter", "lynch", "seer", "sleep", "start", "state", "werewolf", "witch"];
+
+/// A message wrapped with the protocol version it was written against:
+/// `{"version": 2, "action": "lynch", "data": {...}}` or
+/// `{"version": 2, "game_state": {...}}`. Clients may tag a request with a
+/// `request_id`, which is echoed back on the reply to that request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Envelope<T> {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub message: T,
+}
+
+impl<T> Envelope<T> {
+    pub fn new(message: T) -> Envelope<T> {
+        Envelope { version: PROTOCOL_VERSION, request_id: None, message }
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Envelope<T> {
+        self.request_id = request_id;
+        self
+    }
+}
+
+/// A request sent by a client, tagged by `action` with its arguments in `data`.
+/// Once a connection has joined a game, its actions may leave out the lobby
+/// `code` and the server uses the lobby the connection is playing in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", content = "data", rename_all = "lowercase", deny_unknown_fields)]
+pub enum ClientMessage {
+    Join {
+        name: String,
+        secret: String,
+        code: Option<String>,
+    },
+    Rejoin {
+        token: String,
+    },
+    Bodyguard {
+        code: Option<String>,
+        player: String,
+    },
+    /// Make the two `players` lovers, on the first night.
+    Cupid {
+        code: Option<String>,
+        players: Vec<String>,
+    },
+    /// Take the dead hunter's last shot at `player`.
+    Hunter {
+        code: Option<String>,
+        player: String,
+    },
+    Lynch {
+        code: Option<String>,
+        player: String,
+    },
+    Seer {
+        code: Option<String>,
+        player: Option<String>,
+    },
+    Sleep {
+        code: Option<String>,
+    },
+    Start {
+        code: Option<String>,
+        werewolves: u32,
+        bodyguard: Option<bool>,
+        seer: Option<bool>,
+        lycan: Option<bool>,
+        tanner: Option<bool>,
+        witch: Option<bool>,
+        hunter: Option<bool>,
+        cupid: Option<bool>,
+        minion: Option<bool>,
+        masons: Option<u32>,
+    },
+    /// Ask for the caller's view of the game, e.g. after missing a broadcast.
+    State {
+        code: Option<String>,
+    },
+    Werewolf {
+        code: Option<String>,
+        player: String,
+    },
+    /// Use the heal potion on the werewolves' victim and/or the poison on `poison`.
+    Witch {
+        code: Option<String>,
+        heal: Option<bool>,
+        poison: Option<String>,
+    },
+}
+
+impl ClientMessage {
+    /// The name of the action as clients send it.
+    pub fn action(&self) -> &'static str {
+        match self {
+            ClientMessage::Join { .. } => "join",
+            ClientMessage::Rejoin { .. } => "rejoin",
+            ClientMessage::Bodyguard { .. } => "bodyguard",
+            ClientMessage::Cupid { .. } => "cupid",
+            ClientMessage::Hunter { .. } => "hunter",
+            ClientMessage::Lynch { .. } => "lynch",
+            ClientMessage::Seer { .. } => "seer",
+            ClientMessage::Sleep { .. } => "sleep",
+            ClientMessage::Start { .. } => "start",
+            ClientMessage::State { .. } => "state",
+            ClientMessage::Werewolf { .. } => "werewolf",
+            ClientMessage::Witch { .. } => "witch",
+        }
+    }
+
+    /// Serialise the message as a client would send it.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&Envelope::new(self)).unwrap()
+    }
+}
+
+/// The shape of a client message before its action is known. Older clients
+/// that predate versioning omit `version` and speak version 1.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawClientMessage {
+    #[serde(default = "first_version")]
+    version: u32,
+    request_id: Option<String>,
+    action: String,
+    data: Option<Value>,
+}
+
+fn first_version() -> u32 {
+    1
+}
+
+/// Parse a raw request body, rejecting anything that is missing, malformed,
+/// written against another protocol version or carries fields the action
+/// does not understand.
+pub fn parse_client_message(body: Option<&str>) -> Result<Envelope<ClientMessage>, ProtocolError> {
+    let body = match body {
+        Some(body) => body,
+        None => return Err(ProtocolError::new(ErrorCode::InvalidRequest, None, "Missing request body")),
+    };
+    let raw: RawClientMessage = match serde_json::from_str(body) {
+        Ok(raw) => raw,
+        Err(err) => return Err(ProtocolError::new(ErrorCode::InvalidRequest, None, &format!("Invalid request: {}", err))),
+    };
+    let request_id = raw.request_id;
+    parse_action(raw.version, raw.action, raw.data)
+        .map(|message| Envelope { version: PROTOCOL_VERSION, request_id: request_id.clone(), message })
+        .map_err(|err| err.with_request_id(request_id))
+}
+
+fn parse_action(version: u32, name: String, data: Option<Value>) -> Result<ClientMessage, ProtocolError> {
+    let action = Some(name.as_str());
+    if version != PROTOCOL_VERSION {
+        return Err(ProtocolError::new(ErrorCode::UnsupportedVersion, action,
+            &format!("Unsupported protocol version {}, expected {}", version, PROTOCOL_VERSION)));
+    }
+    if !ACTIONS.contains(&name.as_str()) {
+        return Err(ProtocolError::new(ErrorCode::UnknownAction, action, &format!("Unknown action \"{}\"!", name)));
+    }
+    let data = match data {
+        Some(data) => data,
+        None => return Err(ProtocolError::new(ErrorCode::InvalidRequest, action, &format!("Missing data for \"{}\"", name))),
+    };
+
+    let mut tagged = serde_json::Map::new();
+    tagged.insert("action".to_string(), Value::String(name.clone()));
+    tagged.insert("data".to_string(), data);
+    serde_json::from_value(Value::Object(tagged))
+        .map_err(|err| ProtocolError::new(ErrorCode::InvalidRequest, action,
+            &format!("Invalid data for \"{}\": {}", name, err)))
+}
+
+/// A message sent by the server, keyed by its kind:
+/// `{"game_state": {...}}`, `{"ack": {...}}` or `{"error": {...}}`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerMessage {
+    GameState(PlayerView),
+    Ack(AckReply),
+    Error(ErrorReply),
+}
+
+impl ServerMessage {
+    /// The message as it is sent on the wire, including the protocol version.
+    pub fn to_value(&self) -> Value {
+        self.reply_to(None)
+    }
+
+    /// The message as a reply to the client request tagged with `request_id`.
+    pub fn reply_to(&self, request_id: Option<&str>) -> Value {
+        let envelope = Envelope::new(self).with_request_id(request_id.map(|id| id.to_string()));
+        serde_json::to_value(envelope).unwrap()
+    }
+}
+
+/// Confirms a client's request was applied, giving the `GameState.version`
+/// it produced so the client can match it against broadcast states. Joins
+/// also carry the session `token` to send with `rejoin` after reconnecting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AckReply {
+    pub action: String,
+    pub state_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Why a client's request was rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ErrorReply {
+    pub code: ErrorCode,
+    pub action: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct ProtocolError {
+    code: ErrorCode,
+    action: Option<String>,
+    request_id: Option<String>,
+    details: String
+}
+
+impl ProtocolError {
+    fn new(code: ErrorCode, action: Option<&str>, msg: &str) -> ProtocolError {
+        ProtocolError{code, action: action.map(|a| a.to_string()), request_id: None, details: msg.to_string()}
+    }
+
+    fn with_request_id(mut self, request_id: Option<String>) -> ProtocolError {
+        self.request_id = request_id;
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn action(&self) -> Option<&str> {
+        self.action.as_deref()
+    }
+
+    /// The ID the client tagged the rejected request with, if it got far enough to be read.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.details)
+    }
+}
+
+impl Error for ProtocolError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_for(body: &str) -> String {
+        match parse_client_message(Some(body)) {
+            Ok(_) => panic!("Expected {} to be rejected", body),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn rejects_missing_body() {
+        assert_eq!(parse_client_message(None).err().unwrap().to_string(), "Missing request body");
+    }
+
+    #[test]
+    fn rejects_bad_json() {
+        assert!(error_for("{\"version\": 2, \"action\": \"join\"").starts_with("Invalid request:"));
+        assert!(error_for("[]").starts_with("Invalid request:"));
+    }
+
+    #[test]
+    fn rejects_unknown_top_level_fields() {
+        assert!(error_for(r#"{"version": 2, "action": "sleep", "data": {"code": "ABCD"}, "extra": 1}"#).contains("unknown field `extra`"));
+    }
+
+    #[test]
+    fn rejects_unknown_actions() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "fly", "data": {}}"#), "Unknown action \"fly\"!");
+    }
+
+    #[test]
+    fn rejects_other_protocol_versions() {
+        let err = parse_client_message(Some(r#"{"version": 3, "action": "sleep", "data": {"code": "ABCD"}}"#)).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::UnsupportedVersion);
+        assert_eq!(err.action(), Some("sleep"));
+        let err = parse_client_message(Some(r#"{"action": "sleep", "data": {"code": "ABCD"}}"#)).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::UnsupportedVersion);
+        assert!(parse_client_message(Some(r#"{"version": 2, "action": "sleep", "data": {"code": "ABCD"}}"#)).is_ok());
+    }
+
+    #[test]
+    fn errors_carry_code_and_action() {
+        let err = parse_client_message(Some(r#"{"version": 2, "action": "fly", "data": {}}"#)).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::UnknownAction);
+        assert_eq!(err.action(), Some("fly"));
+
+        let err = parse_client_message(Some(r#"{"version": 2, "action": "lynch", "data": {"code": "ABCD"}}"#)).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::InvalidRequest);
+        assert_eq!(err.action(), Some("lynch"));
+
+        let err = parse_client_message(Some("not json")).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::InvalidRequest);
+        assert_eq!(err.action(), None);
+    }
+
+    #[test]
+    fn client_messages_round_trip() {
+        let messages = vec![
+            ClientMessage::Join { name: "Adam".to_string(), secret: "s".to_string(), code: None },
+            ClientMessage::Seer { code: Some("ABCD".to_string()), player: Some("Bob".to_string()) },
+            ClientMessage::Sleep { code: None },
+            ClientMessage::State { code: Some("ABCD".to_string()) },
+            ClientMessage::Rejoin { token: "abc.def".to_string() },
+            ClientMessage::Start { code: Some("ABCD".to_string()), werewolves: 2, bodyguard: Some(true), seer: None, lycan: None, tanner: None, witch: Some(true), hunter: None, cupid: Some(true), minion: None, masons: Some(2) },
+            ClientMessage::Cupid { code: None, players: vec!["p2".to_string(), "p3".to_string()] },
+            ClientMessage::Hunter { code: None, player: "p2".to_string() },
+            ClientMessage::Witch { code: None, heal: Some(true), poison: Some("p3".to_string()) },
+        ];
+        for message in messages {
+            assert_eq!(parse_client_message(Some(&message.to_json())).unwrap().message, message);
+        }
+    }
+
+    /// One message of each kind. The match names every variant without a
+    /// wildcard, so adding an action does not compile until it is listed.
+    fn one_of_each() -> Vec<ClientMessage> {
+        let code = Some("ABCD".to_string());
+        let messages = vec![
+            ClientMessage::Join { name: "Adam".to_string(), secret: "s".to_string(), code: code.clone() },
+            ClientMessage::Rejoin { token: "abc.def".to_string() },
+            ClientMessage::Bodyguard { code: code.clone(), player: "p2".to_string() },
+            ClientMessage::Cupid { code: code.clone(), players: vec!["p2".to_string(), "p3".to_string()] },
+            ClientMessage::Hunter { code: code.clone(), player: "p2".to_string() },
+            ClientMessage::Lynch { code: code.clone(), player: "p2".to_string() },
+            ClientMessage::Seer { code: code.clone(), player: None },
+            ClientMessage::Sleep { code: code.clone() },
+            ClientMessage::Start { code: code.clone(), werewolves: 1, bodyguard: None, seer: None, lycan: None, tanner: None, witch: None, hunter: None, cupid: None, minion: None, masons: None },
+            ClientMessage::State { code: code.clone() },
+            ClientMessage::Werewolf { code: code.clone(), player: "p2".to_string() },
+            ClientMessage::Witch { code, heal: None, poison: None },
+        ];
+        for message in &messages {
+            match message {
+                ClientMessage::Join { .. } | ClientMessage::Rejoin { .. } | ClientMessage::Bodyguard { .. } |
+                ClientMessage::Cupid { .. } | ClientMessage::Hunter { .. } | ClientMessage::Lynch { .. } |
+                ClientMessage::Seer { .. } | ClientMessage::Sleep { .. } | ClientMessage::Start { .. } |
+                ClientMessage::State { .. } | ClientMessage::Werewolf { .. } | ClientMessage::Witch { .. } => {},
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn every_action_is_known() {
+        let actions: Vec<&str> = one_of_each().iter().map(|m| m.action()).collect();
+        assert_eq!(actions, ACTIONS.to_vec());
+        for message in one_of_each() {
+            assert_eq!(parse_client_message(Some(&message.to_json())).unwrap().message, message);
+        }
+    }
+
+    #[test]
+    fn join_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "join"}"#), "Missing data for \"join\"");
+        assert!(error_for(r#"{"version": 2, "action": "join", "data": {"name": "Adam"}}"#).contains("missing field `secret`"));
+        assert!(error_for(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "s", "admin": true}}"#).contains("unknown field `admin`"));
+        assert!(parse_client_message(Some(r#"{"version": 2, "action": "join", "data": {"name": "Adam", "secret": "s"}}"#)).is_ok());
+    }
+
+    #[test]
+    fn rejoin_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "rejoin"}"#), "Missing data for \"rejoin\"");
+        assert!(error_for(r#"{"version": 2, "action": "rejoin", "data": {}}"#).contains("missing field `token`"));
+        assert!(error_for(r#"{"version": 2, "action": "rejoin", "data": {"token": "t", "name": "Adam"}}"#).contains("unknown field `name`"));
+    }
+
+    #[test]
+    fn bodyguard_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "bodyguard"}"#), "Missing data for \"bodyguard\"");
+        assert!(error_for(r#"{"version": 2, "action": "bodyguard", "data": {"code": "ABCD"}}"#).contains("missing field `player`"));
+        assert!(error_for(r#"{"version": 2, "action": "bodyguard", "data": {"code": "ABCD", "player": 3}}"#).contains("invalid type"));
+    }
+
+    #[test]
+    fn lynch_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "lynch"}"#), "Missing data for \"lynch\"");
+        assert!(error_for(r#"{"version": 2, "action": "lynch", "data": {"code": "ABCD"}}"#).contains("missing field `player`"));
+        assert!(error_for(r#"{"version": 2, "action": "lynch", "data": {"code": "ABCD", "player": "Bob", "votes": 2}}"#).contains("unknown field `votes`"));
+    }
+
+    #[test]
+    fn seer_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "seer"}"#), "Missing data for \"seer\"");
+        assert!(error_for(r#"{"version": 2, "action": "seer", "data": {"code": 1}}"#).contains("invalid type"));
+        assert!(error_for(r#"{"version": 2, "action": "seer", "data": {"code": "ABCD", "player": ["Bob"]}}"#).contains("invalid type"));
+        assert!(parse_client_message(Some(r#"{"version": 2, "action": "seer", "data": {"code": "ABCD"}}"#)).is_ok());
+        assert!(parse_client_message(Some(r#"{"version": 2, "action": "seer", "data": {}}"#)).is_ok());
+    }
+
+    #[test]
+    fn sleep_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "sleep"}"#), "Missing data for \"sleep\"");
+        assert!(error_for(r#"{"version": 2, "action": "sleep", "data": {"code": ["ABCD"]}}"#).contains("invalid type"));
+        assert!(error_for(r#"{"version": 2, "action": "sleep", "data": {"code": "ABCD", "player": "Bob"}}"#).contains("unknown field `player`"));
+    }
+
+    #[test]
+    fn start_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "start"}"#), "Missing data for \"start\"");
+        assert!(error_for(r#"{"version": 2, "action": "start", "data": {"code": "ABCD"}}"#).contains("missing field `werewolves`"));
+        assert!(error_for(r#"{"version": 2, "action": "start", "data": {"code": "ABCD", "werewolves": -1}}"#).contains("invalid value"));
+        assert!(error_for(r#"{"version": 2, "action": "start", "data": {"code": "ABCD", "werewolves": 1, "wizard": true}}"#).contains("unknown field `wizard`"));
+    }
+
+    #[test]
+    fn werewolf_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "werewolf"}"#), "Missing data for \"werewolf\"");
+        assert!(error_for(r#"{"version": 2, "action": "werewolf", "data": {"code": "ABCD"}}"#).contains("missing field `player`"));
+        assert!(error_for(r#"{"version": 2, "action": "werewolf", "data": "Bob"}"#).contains("invalid type"));
+    }
+
+    #[test]
+    fn state_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "state"}"#), "Missing data for \"state\"");
+        assert!(error_for(r#"{"version": 2, "action": "state", "data": {"code": 1}}"#).contains("invalid type"));
+        assert!(error_for(r#"{"version": 2, "action": "state", "data": {"code": "ABCD", "player": "p2"}}"#).contains("unknown field `player`"));
+        assert!(parse_client_message(Some(r#"{"version": 2, "action": "state", "data": {}}"#)).is_ok());
+    }
+
+    #[test]
+    fn witch_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "witch"}"#), "Missing data for \"witch\"");
+        assert!(error_for(r#"{"version": 2, "action": "witch", "data": {"heal": "yes"}}"#).contains("invalid type"));
+        assert!(error_for(r#"{"version": 2, "action": "witch", "data": {"poison": ["p2"]}}"#).contains("invalid type"));
+        assert!(error_for(r#"{"version": 2, "action": "witch", "data": {"heal": true, "player": "p2"}}"#).contains("unknown field `player`"));
+        assert!(parse_client_message(Some(r#"{"version": 2, "action": "witch", "data": {}}"#)).is_ok());
+    }
+
+    #[test]
+    fn hunter_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "hunter"}"#), "Missing data for \"hunter\"");
+        assert!(error_for(r#"{"version": 2, "action": "hunter", "data": {"code": "ABCD"}}"#).contains("missing field `player`"));
+        assert!(error_for(r#"{"version": 2, "action": "hunter", "data": {"player": 2}}"#).contains("invalid type"));
+    }
+
+    #[test]
+    fn cupid_rejects_malformed_data() {
+        assert_eq!(error_for(r#"{"version": 2, "action": "cupid"}"#), "Missing data for \"cupid\"");
+        assert!(error_for(r#"{"version": 2, "action": "cupid", "data": {"code": "ABCD"}}"#).contains("missing field `players`"));
+        assert!(error_for(r#"{"version": 2, "action": "cupid", "data": {"players": "p2,p3"}}"#).contains("invalid type"));
+        assert!(error_for(r#"{"version": 2, "action": "cupid", "data": {"players": ["p2", "p3"], "player": "p4"}}"#).contains("unknown field `player`"));
+    }
+
+    #[test]
+    fn request_ids_are_kept() {
+        let envelope = parse_client_message(Some(r#"{"version": 2, "request_id": "r1", "action": "sleep", "data": {"code": "ABCD"}}"#)).unwrap();
+        assert_eq!(envelope.request_id.as_deref(), Some("r1"));
+        assert_eq!(envelope.message, ClientMessage::Sleep { code: Some("ABCD".to_string()) });
+
+        let err = parse_client_message(Some(r#"{"version": 2, "request_id": "r2", "action": "sleep", "data": {"code": 1}}"#)).err().unwrap();
+        assert_eq!(err.request_id(), Some("r2"));
+
+        let ack = ServerMessage::Ack(AckReply { action: "sleep".to_string(), state_version: 4, token: None });
+        assert_eq!(ack.reply_to(Some("r1")), serde_json::json!({
+            "version": PROTOCOL_VERSION,
+            "request_id": "r1",
+            "ack": { "action": "sleep", "state_version": 4 }
+        }));
+    }
+
+    #[test]
+    fn server_messages_keep_wire_format() {
+        let reply = ServerMessage::Error(ErrorReply {
+            code: ErrorCode::LobbyNotFound,
+            action: Some("lynch".to_string()),
+            message: "Game not found".to_string(),
+        });
+        assert_eq!(reply.to_value(), serde_json::json!({
+            "version": PROTOCOL_VERSION,
+            "error": { "code": "LOBBY_NOT_FOUND", "action": "lynch", "message": "Game not found" }
+        }));
+    }
+}
@@ -0,0 +1,77 @@
+use serde_json::Value;
+
+/// Written in place of anything that must not reach the logs.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Fields whose values are credentials, wherever they appear in a message.
+pub const SENSITIVE_FIELDS: [&str; 2] = ["secret", "token"];
+
+/// A copy of a raw request `body` that is safe to log, with the value of
+/// every sensitive field replaced. Bodies that are not JSON cannot be
+/// checked, so only their size is kept.
+pub fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        },
+        Err(_) => format!("<unparseable body of {} bytes>", body.len()),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if SENSITIVE_FIELDS.contains(&key.as_str()) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(field);
+                }
+            }
+        },
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_sensitive_fields_at_any_depth() {
+        let body = r#"{"action": "join", "data": {"name": "Adam", "secret": "hunter2", "nested": [{"token": "abc"}]}}"#;
+        let redacted = redact_body(body);
+        assert!(!redacted.contains("hunter2"));
+        assert!(!redacted.contains("abc"));
+        assert!(redacted.contains("Adam"));
+        assert!(redacted.contains(REDACTED));
+    }
+
+    #[test]
+    fn hides_unparseable_bodies() {
+        assert_eq!(redact_body(r#"{"secret": "hunter2""#), "<unparseable body of 20 bytes>");
+    }
+
+    #[test]
+    fn player_debug_output_hides_secret() {
+        let player = crate::Player {
+            id: "p1".to_string(),
+            connection_id: "c1".to_string(),
+            name: "Adam".to_string(),
+            secret: "hunter2".to_string(),
+            attributes: crate::PlayerAttributes {
+                role: crate::PlayerRole::Villager,
+                team: crate::PlayerTeam::Good,
+                alive: true,
+                visible_to: vec![],
+            },
+            online: true,
+            last_seen: 0,
+        };
+        let output = format!("{:?}", player);
+        assert!(!output.contains("hunter2"));
+        assert!(output.contains(REDACTED));
+    }
+}
@@ -0,0 +1,63 @@
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use argon2::password_hash::SaltString;
+use argon2::password_hash::rand_core::OsRng;
+
+use crate::GameState;
+
+/// Hash a player's rejoin secret with a fresh random salt, giving a PHC
+/// string (`$argon2id$...`) that is safe to store.
+pub fn hash_secret(secret: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default().hash_password(secret.as_bytes(), &salt)
+        .expect("Hashing a secret cannot fail with default parameters")
+        .to_string()
+}
+
+/// Check `candidate` against a hash made by `hash_secret`. The comparison
+/// takes the same time wherever the hashes differ. Anything that is not a
+/// valid hash never matches.
+pub fn verify_secret(stored: &str, candidate: &str) -> bool {
+    match PasswordHash::new(stored) {
+        Ok(hash) => Argon2::default().verify_password(candidate.as_bytes(), &hash).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Whether `stored` is already a hash rather than a plaintext secret.
+pub fn is_hashed(stored: &str) -> bool {
+    PasswordHash::new(stored).is_ok()
+}
+
+/// Hash any secrets saved in plaintext before secrets were hashed, returning
+/// whether anything changed.
+pub fn migrate_secrets(game_state: &mut GameState) -> bool {
+    let mut migrated = false;
+    for player in game_state.players.iter_mut() {
+        if !player.secret.is_empty() && !is_hashed(&player.secret) {
+            player.secret = hash_secret(&player.secret);
+            migrated = true;
+        }
+    }
+    migrated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_verify_only_their_secret() {
+        let hash = hash_secret("hunter2");
+        assert!(is_hashed(&hash));
+        assert!(!hash.contains("hunter2"));
+        assert!(verify_secret(&hash, "hunter2"));
+        assert!(!verify_secret(&hash, "hunter3"));
+        assert_ne!(hash, hash_secret("hunter2"));
+    }
+
+    #[test]
+    fn plaintext_never_verifies() {
+        assert!(!is_hashed("hunter2"));
+        assert!(!verify_secret("hunter2", "hunter2"));
+    }
+}
@@ -0,0 +1,80 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Who a session token was issued to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionClaims {
+    pub lobby_id: String,
+    pub player_id: String,
+}
+
+/// Issues and checks the session tokens players use to rejoin a game from a
+/// new connection. A token is the base64 encoded claims followed by a `.`
+/// and an HMAC-SHA256 signature over them, so it cannot be forged or moved
+/// to another lobby or player without the key.
+pub struct SessionSigner {
+    key: Vec<u8>,
+}
+
+impl SessionSigner {
+    pub fn new(key: &[u8]) -> SessionSigner {
+        SessionSigner { key: key.to_vec() }
+    }
+
+    /// A token binding the holder to the player `player_id` in the lobby `lobby_id`.
+    pub fn issue(&self, lobby_id: &str, player_id: &str) -> String {
+        let claims = SessionClaims { lobby_id: lobby_id.to_string(), player_id: player_id.to_string() };
+        let payload = serde_json::to_vec(&claims).unwrap();
+        let signature = self.mac(&payload).finalize().into_bytes();
+        format!("{}.{}", encode(&payload), encode(&signature))
+    }
+
+    /// The claims in `token`, if it was issued with this signer's key.
+    pub fn verify(&self, token: &str) -> Option<SessionClaims> {
+        let mut parts = token.splitn(2, '.');
+        let payload = decode(parts.next()?)?;
+        let signature = decode(parts.next()?)?;
+        self.mac(&payload).verify_slice(&signature).ok()?;
+        serde_json::from_slice(&payload).ok()
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(text: &str) -> Option<Vec<u8>> {
+    base64::decode_config(text, base64::URL_SAFE_NO_PAD).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_verify_with_their_key_only() {
+        let signer = SessionSigner::new(b"key");
+        let token = signer.issue("ABCD", "p1");
+        assert_eq!(signer.verify(&token), Some(SessionClaims { lobby_id: "ABCD".to_string(), player_id: "p1".to_string() }));
+        assert_eq!(SessionSigner::new(b"other key").verify(&token), None);
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let signer = SessionSigner::new(b"key");
+        let token = signer.issue("ABCD", "p1");
+        let signature = token.split('.').nth(1).unwrap();
+        let forged = format!("{}.{}", encode(br#"{"lobby_id":"ABCD","player_id":"p2"}"#), signature);
+        assert_eq!(signer.verify(&forged), None);
+        assert_eq!(signer.verify("not a token"), None);
+        assert_eq!(signer.verify(""), None);
+    }
+}
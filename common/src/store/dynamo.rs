@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use futures::executor::block_on;
+use rusoto_core::RusotoError;
+use rusoto_dynamodb::{DynamoDb, DynamoDbClient, AttributeValue, PutItemInput, PutItemError, GetItemInput, DeleteItemInput};
+
+use crate::GameState;
+use super::{ConnectionEntry, GameStore, StoreError};
+
+/// A `GameStore` backed by the DynamoDB tables the lambdas are deployed with:
+/// one holding games by lobby ID and one holding connections by connection ID.
+pub struct DynamoStore {
+    client: DynamoDbClient,
+    table_name: String,
+    connections_table_name: String,
+}
+
+impl DynamoStore {
+    pub fn new(table_name: String, connections_table_name: String) -> DynamoStore {
+        DynamoStore {
+            client: DynamoDbClient::new(Default::default()),
+            table_name,
+            connections_table_name,
+        }
+    }
+
+    fn connection_key(connection_id: &str) -> HashMap<String, AttributeValue> {
+        let mut ddb_keys = HashMap::new();
+        ddb_keys.insert("connection_id".to_string(), AttributeValue {
+            s: Some(connection_id.to_string()),
+            ..Default::default()
+        });
+        ddb_keys
+    }
+
+    fn put(&self, game_state: &GameState, condition_expression: String,
+            attribute_values: Option<HashMap<String, AttributeValue>>) -> Result<(), RusotoError<PutItemError>> {
+        let item = serde_dynamodb::to_hashmap(game_state)
+            .map_err(|err| RusotoError::Validation(format!("{:?}", err)))?;
+        block_on(self.client.put_item(PutItemInput {
+            table_name: self.table_name.clone(),
+            condition_expression: Some(condition_expression),
+            item,
+            expression_attribute_values: attribute_values,
+            ..PutItemInput::default()
+        }))?;
+        Ok(())
+    }
+}
+
+impl GameStore for DynamoStore {
+    fn create(&self, game_state: &GameState) -> Result<(), StoreError> {
+        match self.put(game_state, "attribute_not_exists(lobby_id)".to_string(), None) {
+            Ok(()) => Ok(()),
+            Err(RusotoError::Service(PutItemError::ConditionalCheckFailed(_))) => Err(StoreError::AlreadyExists),
+            Err(err) => Err(StoreError::Backend(format!("{:?}", err))),
+        }
+    }
+
+    fn load(&self, lobby_id: &str) -> Result<GameState, StoreError> {
+        let mut ddb_keys = HashMap::new();
+        ddb_keys.insert("lobby_id".to_string(), AttributeValue {
+            s: Some(lobby_id.to_string()),
+            ..Default::default()
+        });
+
+        let item = block_on(self.client.get_item(GetItemInput {
+            table_name: self.table_name.clone(),
+            key: ddb_keys,
+            consistent_read: Some(true),
+            ..GetItemInput::default()
+        }));
+
+        match item {
+            Ok(i) => {
+                match i.item.map(serde_dynamodb::from_hashmap) {
+                    Some(Ok(game_state)) => Ok(game_state),
+                    Some(Err(err)) => Err(StoreError::Corrupted(format!("{}", err))),
+                    None => Err(StoreError::NotFound),
+                }
+            },
+            Err(err) => Err(StoreError::Backend(format!("{:?}", err))),
+        }
+    }
+
+    fn save(&self, mut game_state: GameState) -> Result<GameState, StoreError> {
+        game_state.version += 1;
+        let mut attribute_values = HashMap::default();
+        attribute_values.insert(":version".to_string(), AttributeValue {
+            n: Some(game_state.version.to_string()),
+            ..Default::default()
+        });
+        match self.put(&game_state, "version < :version".to_string(), Some(attribute_values)) {
+            Ok(()) => Ok(game_state),
+            Err(RusotoError::Service(PutItemError::ConditionalCheckFailed(_))) => Err(StoreError::Conflict),
+            Err(err) => Err(StoreError::Backend(format!("{:?}", err))),
+        }
+    }
+
+    fn bind_connection(&self, entry: &ConnectionEntry) -> Result<(), StoreError> {
+        let item = serde_dynamodb::to_hashmap(entry)
+            .map_err(|err| StoreError::Backend(format!("{:?}", err)))?;
+        block_on(self.client.put_item(PutItemInput {
+            table_name: self.connections_table_name.clone(),
+            item,
+            ..PutItemInput::default()
+        })).map_err(|err| StoreError::Backend(format!("{:?}", err)))?;
+        Ok(())
+    }
+
+    fn find_connection(&self, connection_id: &str) -> Result<Option<ConnectionEntry>, StoreError> {
+        let item = block_on(self.client.get_item(GetItemInput {
+            table_name: self.connections_table_name.clone(),
+            key: DynamoStore::connection_key(connection_id),
+            consistent_read: Some(true),
+            ..GetItemInput::default()
+        })).map_err(|err| StoreError::Backend(format!("{:?}", err)))?;
+
+        match item.item.map(serde_dynamodb::from_hashmap) {
+            Some(Ok(entry)) => Ok(Some(entry)),
+            Some(Err(err)) => Err(StoreError::Corrupted(format!("{}", err))),
+            None => Ok(None),
+        }
+    }
+
+    fn unbind_connection(&self, connection_id: &str) -> Result<(), StoreError> {
+        block_on(self.client.delete_item(DeleteItemInput {
+            table_name: self.connections_table_name.clone(),
+            key: DynamoStore::connection_key(connection_id),
+            ..DeleteItemInput::default()
+        })).map_err(|err| StoreError::Backend(format!("{:?}", err)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::GameState;
+use super::{ConnectionEntry, GameStore, StoreError};
+
+/// A `GameStore` that keeps games in process memory, with the same
+/// conditional write behaviour as the DynamoDB store.
+#[derive(Default)]
+pub struct MemoryStore {
+    games: Mutex<HashMap<String, GameState>>,
+    connections: Mutex<HashMap<String, ConnectionEntry>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl GameStore for MemoryStore {
+    fn create(&self, game_state: &GameState) -> Result<(), StoreError> {
+        let mut games = self.games.lock().unwrap();
+        if games.contains_key(&game_state.lobby_id) {
+            return Err(StoreError::AlreadyExists);
+        }
+        games.insert(game_state.lobby_id.clone(), game_state.clone());
+        Ok(())
+    }
+
+    fn load(&self, lobby_id: &str) -> Result<GameState, StoreError> {
+        match self.games.lock().unwrap().get(lobby_id) {
+            Some(game_state) => Ok(game_state.clone()),
+            None => Err(StoreError::NotFound),
+        }
+    }
+
+    fn save(&self, mut game_state: GameState) -> Result<GameState, StoreError> {
+        game_state.version += 1;
+        let mut games = self.games.lock().unwrap();
+        match games.get(&game_state.lobby_id) {
+            Some(stored) if stored.version < game_state.version => {
+                games.insert(game_state.lobby_id.clone(), game_state.clone());
+                Ok(game_state)
+            },
+            _ => Err(StoreError::Conflict),
+        }
+    }
+
+    fn bind_connection(&self, entry: &ConnectionEntry) -> Result<(), StoreError> {
+        self.connections.lock().unwrap().insert(entry.connection_id.clone(), entry.clone());
+        Ok(())
+    }
+
+    fn find_connection(&self, connection_id: &str) -> Result<Option<ConnectionEntry>, StoreError> {
+        Ok(self.connections.lock().unwrap().get(connection_id).cloned())
+    }
+
+    fn unbind_connection(&self, connection_id: &str) -> Result<(), StoreError> {
+        self.connections.lock().unwrap().remove(connection_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{Phase, PhaseName};
+
+    use super::*;
+
+    fn game_state(lobby_id: &str) -> GameState {
+        GameState {
+            lobby_id: lobby_id.to_string(),
+            phase: Phase { name: PhaseName::Lobby, data: HashMap::new() },
+            players: vec![],
+            internal_state: HashMap::new(),
+            ttl: 0,
+            version: 1,
+        }
+    }
+
+    #[test]
+    fn lobby_codes_are_only_created_once() {
+        let store = MemoryStore::new();
+        store.create(&game_state("ABCD")).unwrap();
+        assert_eq!(store.create(&game_state("ABCD")), Err(StoreError::AlreadyExists));
+        assert!(store.create(&game_state("EFGH")).is_ok());
+    }
+
+    #[test]
+    fn saves_bump_the_version() {
+        let store = MemoryStore::new();
+        store.create(&game_state("ABCD")).unwrap();
+        let saved = store.save(store.load("ABCD").unwrap()).unwrap();
+        assert_eq!(saved.version, 2);
+        assert_eq!(store.load("ABCD").unwrap().version, 2);
+    }
+
+    #[test]
+    fn stale_saves_conflict() {
+        let store = MemoryStore::new();
+        store.create(&game_state("ABCD")).unwrap();
+        let stale = store.load("ABCD").unwrap();
+        store.save(store.load("ABCD").unwrap()).unwrap();
+        assert_eq!(store.save(stale).unwrap_err(), StoreError::Conflict);
+        assert_eq!(store.load("ABCD").unwrap().version, 2);
+    }
+
+    #[test]
+    fn saving_a_missing_game_conflicts() {
+        let store = MemoryStore::new();
+        assert_eq!(store.save(game_state("ABCD")).unwrap_err(), StoreError::Conflict);
+        assert_eq!(store.load("ABCD").unwrap_err(), StoreError::NotFound);
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::error::Error;
+
+use crate::GameState;
+
+mod memory;
+pub use memory::MemoryStore;
+
+#[cfg(feature = "aws")]
+mod dynamo;
+#[cfg(feature = "aws")]
+pub use dynamo::DynamoStore;
+
+/// Persistence for game states. Saves are optimistic: a save only succeeds
+/// if nobody else has saved the game since it was loaded.
+pub trait GameStore {
+    /// Store a brand new game, failing with `AlreadyExists` if the lobby ID is taken.
+    fn create(&self, game_state: &GameState) -> Result<(), StoreError>;
+    /// Load the current state of the game with the given lobby ID.
+    fn load(&self, lobby_id: &str) -> Result<GameState, StoreError>;
+    /// Bump the version of `game_state` and store it, failing with `Conflict`
+    /// if the stored game is missing or has already reached that version.
+    fn save(&self, game_state: GameState) -> Result<GameState, StoreError>;
+    /// Record which lobby and player `entry.connection_id` belongs to,
+    /// replacing whatever it was bound to before.
+    fn bind_connection(&self, entry: &ConnectionEntry) -> Result<(), StoreError>;
+    /// Look up what `connection_id` is bound to, if anything.
+    fn find_connection(&self, connection_id: &str) -> Result<Option<ConnectionEntry>, StoreError>;
+    /// Forget `connection_id`, doing nothing if it was never bound.
+    fn unbind_connection(&self, connection_id: &str) -> Result<(), StoreError>;
+}
+
+/// The lobby and player a client connection is playing as, so requests can
+/// be routed without the client naming its lobby.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionEntry {
+    pub connection_id: String,
+    pub lobby_id: String,
+    pub player_id: String,
+    /// Expiry time in seconds since the epoch, matching the game's.
+    pub ttl: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreError {
+    AlreadyExists,
+    NotFound,
+    Conflict,
+    Corrupted(String),
+    Backend(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::AlreadyExists => write!(f, "Lobby already exists"),
+            StoreError::NotFound => write!(f, "Lobby not found"),
+            StoreError::Conflict => write!(f, "Lobby was modified concurrently"),
+            StoreError::Corrupted(details) => write!(f, "Game state corrupted: {}", details),
+            StoreError::Backend(details) => write!(f, "Storage error: {}", details),
+        }
+    }
+}
+
+impl Error for StoreError {}
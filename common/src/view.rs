@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::{GameState, Phase, PhaseName, Player, PlayerAttributes, PlayerRole, PlayerTeam};
+
+/// A game as one player is allowed to see it. Views are built field by field
+/// rather than copied from the `GameState`, so they have nowhere to carry
+/// secrets, connection IDs or `internal_state`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerView {
+    #[serde(rename = "lobbyId")]
+    pub lobby_id: String,
+    pub phase: Phase,
+    pub players: Vec<VisiblePlayer>,
+    pub version: u32,
+}
+
+/// Another player as seen in a `PlayerView`, with their role and team
+/// `Unknown` unless the viewer is allowed to know them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VisiblePlayer {
+    pub id: String,
+    pub name: String,
+    pub attributes: PlayerAttributes,
+    pub online: bool,
+    pub last_seen: u32,
+}
+
+/// What `player` is allowed to see of `game_state`.
+pub fn view_for(game_state: &GameState, player: &Player) -> PlayerView {
+    PlayerView {
+        lobby_id: game_state.lobby_id.clone(),
+        phase: visible_phase(game_state, player),
+        players: game_state.players.iter().map(|p| visible_player(game_state, player, p)).collect(),
+        version: game_state.version,
+    }
+}
+
+fn visible_phase(game_state: &GameState, player: &Player) -> Phase {
+    let mut phase = game_state.phase.clone();
+    if phase.name == PhaseName::Werewolf && ![PlayerRole::Mod, PlayerRole::Werewolf].contains(&player.attributes.role) {
+        phase.data = HashMap::new();
+    }
+    if phase.name == PhaseName::Witch && ![PlayerRole::Mod, PlayerRole::Witch].contains(&player.attributes.role) {
+        phase.data = HashMap::new();
+    }
+    if phase.name == PhaseName::Bodyguard && player.attributes.role == PlayerRole::Bodyguard {
+        let mut phase_data = HashMap::new();
+        phase_data.insert("last_guarded".to_string(), game_state.internal_state.get("last_guarded").cloned().unwrap_or_default());
+        phase.data = phase_data;
+    }
+    phase
+}
+
+/// Whether `first` and `second` are the two lovers, who know each other's roles.
+fn are_lovers(game_state: &GameState, first: &Player, second: &Player) -> bool {
+    matches!(game_state.lovers(), Some((a, b)) if (a == first.id && b == second.id) || (a == second.id && b == first.id))
+}
+
+fn visible_player(game_state: &GameState, viewer: &Player, p: &Player) -> VisiblePlayer {
+    let mut attributes = p.attributes.clone();
+    if game_state.phase.name != PhaseName::End {
+        if p.id != viewer.id && attributes.alive && attributes.role != PlayerRole::Mod && !are_lovers(game_state, viewer, p) {
+            if !attributes.visible_to.contains(&format!("{:?}", viewer.attributes.role)) {
+                attributes.role = PlayerRole::Unknown;
+                attributes.team = PlayerTeam::Unknown;
+            }
+            else if viewer.attributes.role == PlayerRole::Seer {
+                attributes.role = PlayerRole::Unknown;
+                if p.attributes.role == PlayerRole::Lycan {
+                    attributes.team = PlayerTeam::Evil;
+                }
+            }
+        }
+        attributes.visible_to = vec![];
+    }
+    VisiblePlayer {
+        id: p.id.clone(),
+        name: p.name.clone(),
+        attributes,
+        online: p.online,
+        last_seen: p.last_seen,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(id: &str, role: PlayerRole, team: PlayerTeam, visible_to: &[&str]) -> Player {
+        Player {
+            id: id.to_string(),
+            connection_id: format!("connection-{}", id),
+            name: id.to_uppercase(),
+            secret: format!("secret-{}", id),
+            attributes: PlayerAttributes {
+                role,
+                team,
+                alive: true,
+                visible_to: visible_to.iter().map(|v| v.to_string()).collect(),
+            },
+            online: true,
+            last_seen: 0,
+        }
+    }
+
+    fn game_state(phase: PhaseName) -> GameState {
+        let mut internal_state = HashMap::new();
+        internal_state.insert("last_guarded".to_string(), "p2".to_string());
+        GameState {
+            lobby_id: "ABCD".to_string(),
+            phase: Phase { name: phase, data: HashMap::new() },
+            players: vec![
+                player("p1", PlayerRole::Mod, PlayerTeam::Mod, &[]),
+                player("p2", PlayerRole::Werewolf, PlayerTeam::Evil, &["Werewolf"]),
+                player("p3", PlayerRole::Seer, PlayerTeam::Good, &[]),
+                player("p4", PlayerRole::Lycan, PlayerTeam::Good, &["Seer"]),
+            ],
+            internal_state,
+            ttl: 0,
+            version: 3,
+        }
+    }
+
+    #[test]
+    fn views_contain_no_secrets_or_connections() {
+        let game_state = game_state(PhaseName::Day);
+        for viewer in &game_state.players {
+            let json = serde_json::to_string(&view_for(&game_state, viewer)).unwrap();
+            assert!(!json.contains("secret"), "{}", json);
+            assert!(!json.contains("connection"), "{}", json);
+            assert!(!json.contains("last_guarded"), "{}", json);
+        }
+    }
+
+    #[test]
+    fn roles_are_hidden_unless_visible() {
+        let game_state = game_state(PhaseName::Day);
+        let view = view_for(&game_state, &game_state.players[2]);
+        assert_eq!(view.players[0].attributes.role, PlayerRole::Mod);
+        assert_eq!(view.players[1].attributes.role, PlayerRole::Unknown);
+        assert_eq!(view.players[2].attributes.role, PlayerRole::Seer);
+        assert_eq!(view.players[3].attributes.role, PlayerRole::Unknown);
+        assert_eq!(view.players[3].attributes.team, PlayerTeam::Evil);
+
+        let view = view_for(&game_state, &game_state.players[3]);
+        assert_eq!(view.players[1].attributes.team, PlayerTeam::Unknown);
+    }
+
+    #[test]
+    fn lovers_see_each_other() {
+        let mut game_state = game_state(PhaseName::Day);
+        game_state.internal_state.insert(crate::LOVERS.to_string(), "p2,p3".to_string());
+        let view = view_for(&game_state, &game_state.players[2]);
+        assert_eq!(view.players[1].attributes.role, PlayerRole::Werewolf);
+        assert_eq!(view.players[1].attributes.team, PlayerTeam::Evil);
+        assert_eq!(view_for(&game_state, &game_state.players[3]).players[1].attributes.role, PlayerRole::Unknown);
+    }
+
+    #[test]
+    fn player_ids_in_visible_to_reveal_nothing() {
+        let mut game_state = game_state(PhaseName::Day);
+        game_state.players[2].id = "Werewolf".to_string();
+        let view = view_for(&game_state, &game_state.players[2]);
+        assert_eq!(view.players[1].attributes.role, PlayerRole::Unknown);
+    }
+
+    #[test]
+    fn bodyguards_see_who_they_last_guarded() {
+        let mut game_state = game_state(PhaseName::Bodyguard);
+        game_state.players[3].attributes.role = PlayerRole::Bodyguard;
+        let view = view_for(&game_state, &game_state.players[3]);
+        assert_eq!(view.phase.data["last_guarded"], "p2");
+        assert!(view_for(&game_state, &game_state.players[2]).phase.data.is_empty());
+    }
+}
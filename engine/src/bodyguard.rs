@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use common::ErrorCode;
+
+use crate::RuleError;
+
+pub fn move_to_werewolf(mut game_state: common::GameState, actor: &str, protect_player_id: String)
+        -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Bodyguard {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Bodyguard {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not the bodyguard!"));
+    }
+    let protect_player: Vec<common::Player> = game_state.players.clone().into_iter()
+        .filter(|p| p.id == protect_player_id && p.attributes.alive).collect();
+    if protect_player.len() != 1 || protect_player_id == players[0].id || 
+        game_state.internal_state.get("last_guarded").unwrap_or(&"".to_string()).clone() == protect_player_id {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "Invalid player to protect!"));
+    }
+    game_state.internal_state.insert("last_guarded".to_string(), protect_player_id);
+    game_state.phase = common::Phase {
+        name: common::PhaseName::Werewolf,
+        data: HashMap::new(),
+    };
+    Ok(game_state)
+}
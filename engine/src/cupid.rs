@@ -0,0 +1,28 @@
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::night_phase;
+
+/// Make the two `lovers` fall in love on the first night. From then on each
+/// can see the other's role, and neither outlives the other.
+pub fn choose_lovers(mut game_state: common::GameState, actor: &str, lovers: Vec<String>) -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Cupid {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Cupid {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not cupid!"));
+    }
+    let valid = lovers.len() == 2 && lovers[0] != lovers[1] && lovers.iter().all(|lover| game_state.players.iter()
+        .any(|p| &p.id == lover && p.attributes.alive && p.attributes.role != common::PlayerRole::Mod));
+    if !valid {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "Choose two different living players!"));
+    }
+
+    game_state.internal_state.insert(common::LOVERS.to_string(), lovers.join(","));
+    game_state.phase = night_phase(&game_state);
+    Ok(game_state)
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+pub fn check_game_over(game_state: &common::GameState) -> Option<Vec<common::PlayerTeam>> {
+    if let Some((first, second)) = game_state.lovers() {
+        let living: Vec<&common::Player> = game_state.players.iter()
+            .filter(|p| p.attributes.alive && p.attributes.role != common::PlayerRole::Mod)
+            .collect();
+        let teams: Vec<&common::PlayerTeam> = living.iter().map(|p| &p.attributes.team).collect();
+        if living.len() == 2 && living.iter().all(|p| p.id == first || p.id == second) &&
+            teams.contains(&&common::PlayerTeam::Good) && teams.contains(&&common::PlayerTeam::Evil) {
+            return Some(vec![common::PlayerTeam::Lovers]);
+        }
+    }
+    let players = game_state.players.clone();
+    let good_players: Vec<common::Player> = players.clone().into_iter().filter(|p| p.attributes.team == common::PlayerTeam::Good && p.attributes.alive).collect();
+    // Only the werewolves kill, so evil players such as the minion count for
+    // neither side: the werewolves win once they match the good players
+    // alone, and lose once they are all dead.
+    let werewolves = living_players_with_role(common::PlayerRole::Werewolf, players.clone()) as usize;
+    let mut winners = None;
+    if werewolves >= good_players.len() || werewolves == 0 {
+        let mut teams = vec![];
+        if players.clone().into_iter().filter(|p| p.attributes.role == common::PlayerRole::Tanner).count() > 0 && 
+            living_players_with_role(common::PlayerRole::Tanner, players.clone()) < 1 {
+            teams.push(common::PlayerTeam::Tanner);
+        }
+        match werewolves {
+            0 => {
+                teams.push(common::PlayerTeam::Good);
+            },
+            _ => {
+                teams.push(common::PlayerTeam::Evil);
+            }
+        };
+        winners = Some(teams);
+    }
+    winners
+}
+
+pub fn living_players_with_role(role: common::PlayerRole, players: Vec<common::Player>) -> u32 {
+    players.into_iter().filter(|p| p.attributes.role == role && p.attributes.alive).count() as u32
+}
+
+/// Whether the bodyguard, if still alive, protected `player_id` tonight.
+pub fn is_guarded(game_state: &common::GameState, player_id: &str) -> bool {
+    game_state.internal_state.get("last_guarded").map(|p| p.as_str()) == Some(player_id) &&
+        living_players_with_role(common::PlayerRole::Bodyguard, game_state.players.clone()) > 0
+}
+
+/// Where the game goes once the deaths from a lynch or a night are dealt with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Next {
+    Day,
+    Night,
+}
+
+impl Next {
+    /// The phase this leads to, given who is still alive.
+    pub fn phase(self, game_state: &common::GameState) -> common::Phase {
+        match self {
+            Next::Day => common::Phase {
+                name: common::PhaseName::Day,
+                data: HashMap::new(),
+            },
+            Next::Night => night_phase(game_state),
+        }
+    }
+
+    pub fn parse(next: &str) -> Option<Next> {
+        match next {
+            "Day" => Some(Next::Day),
+            "Night" => Some(Next::Night),
+            _ => None,
+        }
+    }
+}
+
+/// The first phase of the night: Cupid's turn if they are alive and have yet
+/// to choose the lovers, which can only be on the first night, then the
+/// seer's, then the bodyguard's, then the werewolves'.
+pub fn night_phase(game_state: &common::GameState) -> common::Phase {
+    let players = &game_state.players;
+    let name = if living_players_with_role(common::PlayerRole::Cupid, players.to_vec()) > 0 &&
+        !game_state.internal_state.contains_key(common::LOVERS) {
+        common::PhaseName::Cupid
+    }
+    else if living_players_with_role(common::PlayerRole::Seer, players.to_vec()) > 0 {
+        common::PhaseName::Seer
+    }
+    else if living_players_with_role(common::PlayerRole::Bodyguard, players.to_vec()) > 0 {
+        common::PhaseName::Bodyguard
+    }
+    else {
+        common::PhaseName::Werewolf
+    };
+    common::Phase {
+        name,
+        data: HashMap::new(),
+    }
+}
+
+/// Kill the players in `deaths`, and the lover of any of them, and move on to
+/// `next`, unless that ends the game or the hunter is among them and gets to
+/// take a shot first. Players who were already dead are ignored, so they can
+/// neither shoot again nor take their lover with them twice.
+pub fn after_deaths(mut game_state: common::GameState, deaths: &[String], next: Next) -> common::GameState {
+    let mut deaths: Vec<String> = deaths.iter()
+        .filter(|id| game_state.players.iter().any(|p| &&p.id == id && p.attributes.alive))
+        .cloned()
+        .collect();
+    if let Some((first, second)) = game_state.lovers() {
+        let partner = if deaths.contains(&first) { Some(second) } else if deaths.contains(&second) { Some(first) } else { None };
+        if let Some(partner) = partner {
+            if game_state.players.iter().any(|p| p.id == partner && p.attributes.alive) && !deaths.contains(&partner) {
+                deaths.push(partner);
+            }
+        }
+    }
+    for player in game_state.players.iter_mut().filter(|p| deaths.contains(&p.id)) {
+        player.attributes.alive = false;
+    }
+    let hunter = game_state.players.iter()
+        .find(|p| deaths.contains(&p.id) && p.attributes.role == common::PlayerRole::Hunter);
+    game_state.phase = match (hunter, check_game_over(&game_state)) {
+        (Some(hunter), _) => {
+            let mut phase_data = HashMap::new();
+            phase_data.insert("hunter".to_string(), hunter.id.clone());
+            phase_data.insert("next".to_string(), format!("{:?}", next));
+            common::Phase {
+                name: common::PhaseName::Hunter,
+                data: phase_data,
+            }
+        },
+        (None, Some(winners)) => game_over_phase(winners),
+        (None, None) => next.phase(&game_state),
+    };
+    game_state
+}
+
+/// Kill the players in `deaths` at the end of the night and start the next day.
+pub fn end_night(game_state: common::GameState, deaths: &[String]) -> common::GameState {
+    after_deaths(game_state, deaths, Next::Day)
+}
+
+/// The `End` phase announcing `winners`.
+pub fn game_over_phase(winners: Vec<common::PlayerTeam>) -> common::Phase {
+    let mut phase_data = HashMap::new();
+    phase_data.insert("winner".to_string(), winners.into_iter().map(|w| format!("{:?}", w)).collect::<Vec<String>>().join(", "));
+    common::Phase {
+        name: common::PhaseName::End,
+        data: phase_data,
+    }
+}
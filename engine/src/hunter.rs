@@ -0,0 +1,26 @@
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::{after_deaths, Next};
+
+/// Take the hunter's last shot at `shoot_player_id` after they have died,
+/// then carry on to the phase their death interrupted.
+pub fn shoot(game_state: common::GameState, actor: &str, shoot_player_id: String) -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Hunter {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if game_state.phase.data.get("hunter") != Some(&players[0].id) {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not the hunter!"));
+    }
+    let valid = game_state.players.iter().any(|p| p.id == shoot_player_id && p.attributes.alive &&
+        p.attributes.role != common::PlayerRole::Mod);
+    if !valid {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "Invalid player to shoot!"));
+    }
+    let next = game_state.phase.data.get("next").and_then(|next| Next::parse(next)).unwrap_or(Next::Day);
+    Ok(after_deaths(game_state, &[shoot_player_id], next))
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use common::ErrorCode;
+
+use crate::RuleError;
+
+/// Create the state for a brand new lobby with `actor` as its only player.
+/// The caller hashes `secret` first; the rules only store it.
+pub fn new_game(lobby_id: String, actor: &str, name: String, secret: String, ttl: u32) -> Result<common::GameState, RuleError> {
+    validate_player(&name, &secret)?;
+    Ok(common::GameState {
+        lobby_id,
+        phase: common::Phase {
+            name: common::PhaseName::Lobby,
+            data: HashMap::new(),
+        },
+        players: vec![common::Player{
+            id: next_player_id(&[]),
+            connection_id: actor.to_string(),
+            name,
+            secret,
+            attributes: common::PlayerAttributes {
+                role: common::PlayerRole::Unknown,
+                team: common::PlayerTeam::Unknown,
+                alive: true,
+                visible_to: vec!["All".to_string()],
+            },
+            online: true,
+            last_seen: 0,
+        }],
+        internal_state: HashMap::new(),
+        version: 1,
+        ttl,
+    })
+}
+
+/// Add a new player called `name` to the lobby, with their already hashed
+/// `secret`. Players coming back under their old name are checked by the
+/// caller and rejoined with `rejoin_game` instead.
+pub fn join_game(mut game_state: common::GameState, actor: &str, name: String, secret: String) -> Result<common::GameState, RuleError> {
+    validate_player(&name, &secret)?;
+    if game_state.players.iter().any(|player| player.name == name) {
+        return Err(RuleError::new(ErrorCode::InvalidPlayer, "That name is taken"));
+    }
+    else if game_state.phase.name == common::PhaseName::Lobby {
+        game_state.players.push(common::Player{
+            id: next_player_id(&game_state.players),
+            connection_id: actor.to_string(),
+            name,
+            secret,
+            attributes: common::PlayerAttributes {
+                role: common::PlayerRole::Unknown,
+                team: common::PlayerTeam::Unknown,
+                alive: true,
+                visible_to: vec!["All".to_string()],
+            },
+            online: true,
+            last_seen: 0,
+        });
+    }
+    else {
+        return Err(RuleError::new(ErrorCode::GameInProgress, "Error cannot join an in-progress game"))
+    }
+    Ok(game_state)
+}
+
+/// Move the player with the ID `player_id`, whose identity the caller has
+/// already checked, onto the connection `actor`.
+pub fn rejoin_game(mut game_state: common::GameState, actor: &str, player_id: String) -> Result<common::GameState, RuleError> {
+    let position = match game_state.players.iter().position(|player| player.id == player_id) {
+        Some(position) => position,
+        None => return Err(RuleError::new(ErrorCode::InvalidToken, "Session does not match a player in this game")),
+    };
+    let mut player = game_state.players.remove(position);
+    player.connection_id = actor.to_string();
+    player.online = true;
+    game_state.players.push(player);
+    Ok(game_state)
+}
+
+/// A player ID not yet used in the lobby. Players are never removed, so IDs
+/// are never reused either.
+fn next_player_id(players: &[common::Player]) -> String {
+    (players.len() + 1..).map(|n| format!("p{}", n))
+        .find(|id| players.iter().all(|p| &p.id != id))
+        .unwrap()
+}
+
+fn validate_player(name: &str, secret: &str) -> Result<(), RuleError> {
+    if name.is_empty() {
+        return Err(RuleError::new(ErrorCode::InvalidPlayer, "Empty first name"));
+    }
+    else if secret.is_empty() {
+        return Err(RuleError::new(ErrorCode::InvalidPlayer, "Empty secret"));
+    }
+    Ok(())
+}
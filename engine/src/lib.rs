@@ -0,0 +1,135 @@
+#[macro_use]
+extern crate log;
+extern crate rand;
+
+use std::fmt;
+use std::error::Error;
+
+use common::ErrorCode;
+
+mod bodyguard;
+mod cupid;
+mod hunter;
+mod join;
+mod lynch;
+mod presence;
+mod seer;
+mod sleep;
+mod start;
+mod werewolf;
+mod witch;
+
+mod helpers;
+
+pub use join::new_game;
+pub use helpers::{check_game_over, living_players_with_role};
+
+/// An action a player can take against a game.
+#[derive(Clone, Debug)]
+pub enum Action {
+    Bodyguard {
+        player: String,
+    },
+    Cupid {
+        players: Vec<String>,
+    },
+    Disconnect {
+        at: u32,
+    },
+    Hunter {
+        player: String,
+    },
+    /// Join as a new player, with `secret` already hashed by the caller.
+    Join {
+        name: String,
+        secret: String,
+    },
+    Lynch {
+        player: String,
+    },
+    Rejoin {
+        player: String,
+    },
+    Seer {
+        player: Option<String>,
+    },
+    Sleep {},
+    Start {
+        werewolves: u32,
+        bodyguard: Option<bool>,
+        seer: Option<bool>,
+        lycan: Option<bool>,
+        tanner: Option<bool>,
+        witch: Option<bool>,
+        hunter: Option<bool>,
+        cupid: Option<bool>,
+        minion: Option<bool>,
+        masons: Option<u32>,
+    },
+    Werewolf {
+        player: String,
+    },
+    Witch {
+        heal: bool,
+        poison: Option<String>,
+    },
+}
+
+/// Apply `action`, taken by the player on the connection `actor`, to
+/// `game_state` returning the resulting state. Actions name their target by
+/// player ID. The caller is responsible for persisting the new state.
+pub fn apply(game_state: common::GameState, actor: &str, action: Action) -> Result<common::GameState, RuleError> {
+    match action {
+        Action::Bodyguard { player } => bodyguard::move_to_werewolf(game_state, actor, player),
+        Action::Cupid { players } => cupid::choose_lovers(game_state, actor, players),
+        Action::Disconnect { at } => presence::disconnect(game_state, actor, at),
+        Action::Hunter { player } => hunter::shoot(game_state, actor, player),
+        Action::Join { name, secret } => join::join_game(game_state, actor, name, secret),
+        Action::Lynch { player } => lynch::move_to_sleep(game_state, actor, player),
+        Action::Rejoin { player } => join::rejoin_game(game_state, actor, player),
+        Action::Seer { player } => seer::move_to_werewolf(game_state, actor, player),
+        Action::Sleep {} => sleep::move_to_sleep(game_state, actor),
+        Action::Start { werewolves, bodyguard, seer, lycan, tanner, witch, hunter, cupid, minion, masons } => start::move_to_day(game_state, actor, start::Roles {
+            werewolves,
+            bodyguard: bodyguard.unwrap_or(false),
+            seer: seer.unwrap_or(true),
+            lycan: lycan.unwrap_or(false),
+            tanner: tanner.unwrap_or(false),
+            witch: witch.unwrap_or(false),
+            hunter: hunter.unwrap_or(false),
+            cupid: cupid.unwrap_or(false),
+            minion: minion.unwrap_or(false),
+            masons: masons.unwrap_or(0),
+        }),
+        Action::Werewolf { player } => werewolf::werewolf(game_state, actor, player),
+        Action::Witch { heal, poison } => witch::witch(game_state, actor, heal, poison),
+    }
+}
+
+#[derive(Debug)]
+pub struct RuleError {
+    code: ErrorCode,
+    details: String
+}
+
+impl RuleError {
+    pub fn new(code: ErrorCode, msg: &str) -> RuleError {
+        RuleError{code, details: msg.to_string()}
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",self.details)
+    }
+}
+
+impl Error for RuleError {
+    fn description(&self) -> &str {
+        &self.details
+    }
+}
@@ -0,0 +1,27 @@
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::{after_deaths, Next};
+
+pub fn move_to_sleep(game_state: common::GameState, actor: &str, lynched_player: String)
+        -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Day {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Mod {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not the moderator!"));
+    }
+    let killing_player: Vec<common::Player> = game_state.players.clone().into_iter()
+        .filter(|p| p.id == lynched_player).collect();
+    if killing_player.len() != 1 || killing_player[0].attributes.role == common::PlayerRole::Mod {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "Invalid player to lynch!"));
+    }
+    else if !killing_player[0].attributes.alive {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "Player is already dead!"));
+    }
+    Ok(after_deaths(game_state, &[lynched_player], Next::Night))
+}
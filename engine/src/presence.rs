@@ -0,0 +1,14 @@
+use common::ErrorCode;
+
+use crate::RuleError;
+
+/// Mark the player on the connection `actor` as offline since `at`.
+pub fn disconnect(mut game_state: common::GameState, actor: &str, at: u32) -> Result<common::GameState, RuleError> {
+    let player = match game_state.players.iter_mut().find(|p| p.connection_id == actor) {
+        Some(player) => player,
+        None => return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor))),
+    };
+    player.online = false;
+    player.last_seen = at;
+    Ok(game_state)
+}
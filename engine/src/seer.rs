@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::living_players_with_role;
+
+pub fn move_to_werewolf(mut game_state: common::GameState, actor: &str, see_player_id: Option<String>)
+        -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Seer {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Seer {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not the seer!"));
+    }
+    game_state.players = get_new_players(see_player_id, game_state.clone())?;
+    if living_players_with_role(common::PlayerRole::Bodyguard, game_state.clone().players) > 0 {
+        game_state.phase = common::Phase {
+            name: common::PhaseName::Bodyguard,
+            data: HashMap::new(),
+        };
+    }
+    else {
+        game_state.phase = common::Phase {
+            name: common::PhaseName::Werewolf,
+            data: HashMap::new(),
+        };
+    }
+    Ok(game_state)
+}
+
+fn get_new_players(see_player_id: Option<String>, game_state: common::GameState) -> Result<Vec<common::Player>, RuleError> {
+    if let Some(see_player_id) = see_player_id {
+        let see_player: Vec<common::Player> = game_state.players.clone().into_iter()
+            .filter(|p| p.id == see_player_id).collect();
+        if see_player.len() != 1 {
+            return Err(RuleError::new(ErrorCode::InvalidTarget, "Invalid player to see!"));
+        }
+        else if see_player[0].attributes.visible_to.contains(&format!("{:?}", common::PlayerRole::Seer)) || !see_player[0].attributes.alive {
+            return Err(RuleError::new(ErrorCode::InvalidTarget, "Player is already seen!"));
+        }
+        let mut new_players = game_state.players;
+        new_players.retain(|p| p.id != see_player_id);
+        let mut new_attributes = see_player[0].attributes.clone();
+        new_attributes.visible_to.push(format!("{:?}", common::PlayerRole::Seer));
+        let mut new_seen_player = see_player[0].clone();
+        new_seen_player.attributes = see_player[0].attributes.clone();
+        new_seen_player.attributes = new_attributes;
+        new_players.push(new_seen_player);
+        return Ok(new_players);
+    }
+    Ok(game_state.players)
+}
@@ -0,0 +1,19 @@
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::night_phase;
+
+pub fn move_to_sleep(mut game_state: common::GameState, actor: &str) -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Day {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Mod {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not the moderator!"));
+    }
+    game_state.phase = night_phase(&game_state);
+    Ok(game_state)
+}
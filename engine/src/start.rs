@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use common::ErrorCode;
+
+use crate::RuleError;
+
+/// The roles to deal when the game starts, besides the moderator and villagers.
+pub struct Roles {
+    pub werewolves: u32,
+    pub bodyguard: bool,
+    pub seer: bool,
+    pub lycan: bool,
+    pub tanner: bool,
+    pub witch: bool,
+    pub hunter: bool,
+    pub cupid: bool,
+    pub minion: bool,
+    /// Either none, or a group of two or three who know each other.
+    pub masons: u32,
+}
+
+pub fn move_to_day(mut game_state: common::GameState, actor: &str, roles: Roles) -> Result<common::GameState, RuleError> {
+    let Roles { werewolves, bodyguard, seer, lycan, tanner, witch, hunter, cupid, minion, masons } = roles;
+    if !game_state.players.iter().any(|p| p.connection_id == actor) {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Lobby {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if ![0, 2, 3].contains(&masons) {
+        return Err(RuleError::new(ErrorCode::InvalidRequest, "Masons must come in pairs or threes!"));
+    }
+    let mut roles_count = werewolves + masons + 1;
+    if bodyguard { roles_count += 1 }
+    if seer { roles_count += 1 }
+    if lycan { roles_count += 1 }
+    if tanner { roles_count += 1 }
+    if witch { roles_count += 1 }
+    if hunter { roles_count += 1 }
+    if cupid { roles_count += 1 }
+    if minion { roles_count += 1 }
+    if roles_count > game_state.players.len() as u32 {
+        error!("Roles: {}, Players: {}", roles_count, game_state.players.len());
+        return Err(RuleError::new(ErrorCode::TooManyRoles, "More roles than players!"));
+    }
+    
+    let mut roles: Vec<common::PlayerAttributes> = vec![];
+    let num_villagers = game_state.players.len() as u32 - roles_count;
+    if seer {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Seer,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if bodyguard {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Bodyguard,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if lycan {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Lycan,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if tanner {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Tanner,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if witch {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Witch,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if hunter {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Hunter,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if cupid {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Cupid,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    if minion {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Minion,
+            team: common::PlayerTeam::Evil,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+    for _ in 0..werewolves {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Werewolf,
+            team: common::PlayerTeam::Evil,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod), format!("{:?}", common::PlayerRole::Werewolf),
+                format!("{:?}", common::PlayerRole::Minion)],
+        });
+    }
+    for _ in 0..masons {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Mason,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod), format!("{:?}", common::PlayerRole::Mason)],
+        });
+    }
+    for _ in 0..num_villagers {
+        roles.push(common::PlayerAttributes {
+            role: common::PlayerRole::Villager,
+            team: common::PlayerTeam::Good,
+            alive: true,
+            visible_to: vec![format!("{:?}", common::PlayerRole::Mod)],
+        });
+    }
+
+
+    let new_players = create_new_players(game_state.clone(), roles, actor);
+
+    game_state.players = new_players;
+    game_state.phase = common::Phase {
+        name: common::PhaseName::Day,
+        data: HashMap::new(),
+    };
+
+    Ok(game_state)
+}
+
+fn create_new_players(game_state: common::GameState, mut roles: Vec<common::PlayerAttributes>, actor: &str) -> Vec<common::Player> {
+    let mut new_players = vec![];
+    let mut rng = rand::thread_rng();
+    for player in &game_state.players {
+        let mut new_player = player.clone();
+        if player.connection_id == actor {
+            new_player.attributes = common::PlayerAttributes {
+                role: common::PlayerRole::Mod,
+                team: common::PlayerTeam::Unknown,
+                alive: true,
+                visible_to: vec!["All".to_string()],
+            };
+        }
+        else {
+            let role = roles.remove(rng.gen_range(0, roles.len()));
+            new_player.attributes = role;
+        }
+        new_players.push(new_player);
+    }
+    new_players
+}
@@ -0,0 +1,47 @@
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::{end_night, is_guarded, living_players_with_role};
+use crate::witch::wake_witch;
+
+pub fn werewolf(mut game_state: common::GameState, actor: &str, eat_player_id: String)
+        -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Werewolf {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Werewolf {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not a werewolf!"));
+    }
+    let eat_player: Vec<common::Player> = game_state.players.clone().into_iter()
+        .filter(|p| p.id == eat_player_id).collect();
+    // Anyone but the pack may be eaten, so that trying every player does not
+    // give away who else is evil.
+    if eat_player.len() != 1 || !eat_player[0].attributes.alive ||
+        [common::PlayerRole::Mod, common::PlayerRole::Werewolf].contains(&eat_player[0].attributes.role) {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "Invalid player to eat!"));
+    }
+    let num_werewolves = game_state.players.clone().into_iter()
+        .filter(|p| {
+            p.attributes.role == common::PlayerRole::Werewolf &&
+            p.attributes.alive
+        }).count();
+    game_state.phase.data.insert(players[0].clone().id, eat_player[0].clone().id);
+
+    if game_state.phase.data.len() == num_werewolves {
+        let num_other_votes = game_state.phase.data.clone().into_iter()
+            .filter(|(_, value)| value.clone() != eat_player_id).count();
+        if num_other_votes < 1 {
+            if living_players_with_role(common::PlayerRole::Witch, game_state.players.clone()) > 0 {
+                game_state.phase = wake_witch(&game_state, eat_player_id);
+                return Ok(game_state);
+            }
+            let deaths = if is_guarded(&game_state, &eat_player_id) { vec![] } else { vec![eat_player_id] };
+            return Ok(end_night(game_state, &deaths));
+        }
+    }
+    Ok(game_state)
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use common::ErrorCode;
+
+use crate::RuleError;
+use crate::helpers::{end_night, is_guarded};
+
+/// Keys in `GameState.internal_state` recording that a potion has been used.
+const HEAL_USED: &str = "witch_heal_used";
+const POISON_USED: &str = "witch_poison_used";
+
+/// The `Witch` phase showing the witch the werewolves' `victim` and which of
+/// her potions are left, as a comma separated list.
+pub fn wake_witch(game_state: &common::GameState, victim: String) -> common::Phase {
+    let potions: Vec<&str> = [("heal", HEAL_USED), ("poison", POISON_USED)].iter()
+        .filter(|(_, used)| !game_state.internal_state.contains_key(*used))
+        .map(|(potion, _)| *potion)
+        .collect();
+    let mut phase_data = HashMap::new();
+    phase_data.insert("victim".to_string(), victim);
+    phase_data.insert("potions".to_string(), potions.join(","));
+    common::Phase {
+        name: common::PhaseName::Witch,
+        data: phase_data,
+    }
+}
+
+/// End the night, with the witch optionally saving the werewolves' victim
+/// with her heal potion and killing `poison_player_id` with her poison.
+/// Each potion can only be used once per game.
+pub fn witch(mut game_state: common::GameState, actor: &str, heal: bool, poison_player_id: Option<String>)
+        -> Result<common::GameState, RuleError> {
+    let players: Vec<common::Player> = game_state.players.clone().into_iter().filter(|p| p.connection_id == actor).collect();
+    if players.len() != 1 {
+        return Err(RuleError::new(ErrorCode::PlayerNotFound, &format!("Could not find player with connection ID: {:?}", actor)));
+    }
+    else if game_state.phase.name != common::PhaseName::Witch {
+        return Err(RuleError::new(ErrorCode::NotYourTurn, "Not a valid transition!"));
+    }
+    else if players[0].attributes.role != common::PlayerRole::Witch {
+        return Err(RuleError::new(ErrorCode::WrongRole, "You are not the witch!"));
+    }
+    let victim = game_state.phase.data.get("victim").cloned().unwrap_or_default();
+    if heal && (game_state.internal_state.contains_key(HEAL_USED) || victim.is_empty()) {
+        return Err(RuleError::new(ErrorCode::InvalidTarget, "You cannot heal anyone!"));
+    }
+    if let Some(poison_player_id) = &poison_player_id {
+        let valid = game_state.players.iter().any(|p| &p.id == poison_player_id && p.attributes.alive &&
+            p.attributes.role != common::PlayerRole::Mod && p.id != players[0].id);
+        if game_state.internal_state.contains_key(POISON_USED) || !valid {
+            return Err(RuleError::new(ErrorCode::InvalidTarget, "Invalid player to poison!"));
+        }
+    }
+
+    let mut deaths = vec![];
+    // Healing a victim the bodyguard already saved does not use up the potion.
+    let guarded = is_guarded(&game_state, &victim);
+    if heal && !guarded {
+        game_state.internal_state.insert(HEAL_USED.to_string(), victim);
+    }
+    else if !heal && !guarded && !victim.is_empty() {
+        deaths.push(victim);
+    }
+    if let Some(poison_player_id) = poison_player_id {
+        game_state.internal_state.insert(POISON_USED.to_string(), poison_player_id.clone());
+        deaths.push(poison_player_id);
+    }
+    Ok(end_night(game_state, &deaths))
+}
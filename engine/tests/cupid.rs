@@ -0,0 +1,72 @@
+use common::{ErrorCode, PhaseName, PlayerRole};
+use engine::Action;
+
+mod support;
+use support::{alive, day, night, village};
+
+/// The first day, with enough villagers that a single death does not end the game.
+fn first_day() -> common::GameState {
+    day(&village(PlayerRole::Cupid, 4))
+}
+
+fn choose(game_state: common::GameState, actor: &str, lovers: &[&str]) -> Result<common::GameState, engine::RuleError> {
+    engine::apply(game_state, actor, Action::Cupid { players: lovers.iter().map(|l| l.to_string()).collect() })
+}
+
+/// The first night, once Cupid has made `lovers` fall in love.
+fn in_love(lovers: &[&str]) -> common::GameState {
+    let game_state = engine::apply(first_day(), "c1", Action::Sleep {}).unwrap();
+    choose(game_state, "c3", lovers).unwrap()
+}
+
+#[test]
+fn cupid_only_wakes_on_the_first_night() {
+    let game_state = engine::apply(first_day(), "c1", Action::Sleep {}).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Cupid);
+
+    let game_state = choose(game_state, "c3", &["p4", "p5"]).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Werewolf);
+    assert_eq!(game_state.lovers(), Some(("p4".to_string(), "p5".to_string())));
+    let view = common::view_for(&game_state, &game_state.players[3]);
+    assert_eq!(view.players[4].attributes.role, PlayerRole::Villager);
+    assert_eq!(view.players[5].attributes.role, PlayerRole::Unknown);
+
+    let game_state = engine::apply(game_state, "c2", Action::Werewolf { player: "p6".to_string() }).unwrap();
+    let game_state = engine::apply(game_state, "c1", Action::Sleep {}).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Werewolf);
+}
+
+#[test]
+fn lovers_die_together() {
+    let game_state = engine::apply(in_love(&["p4", "p5"]), "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    assert!(!alive(&game_state, "p4"));
+    assert!(!alive(&game_state, "p5"));
+    assert_eq!(game_state.phase.name, PhaseName::Day);
+
+    let game_state = engine::apply(in_love(&["p4", "p5"]), "c2", Action::Werewolf { player: "p6".to_string() }).unwrap();
+    let game_state = engine::apply(game_state, "c1", Action::Lynch { player: "p5".to_string() }).unwrap();
+    assert!(!alive(&game_state, "p4"));
+    assert!(alive(&game_state, "p3"));
+}
+
+#[test]
+fn a_werewolf_and_a_villager_in_love_win_alone() {
+    let mut game_state = night(&[PlayerRole::Mod, PlayerRole::Werewolf, PlayerRole::Cupid, PlayerRole::Villager]);
+    game_state.phase.name = PhaseName::Cupid;
+    let game_state = choose(game_state, "c3", &["p2", "p4"]).unwrap();
+
+    let game_state = engine::apply(game_state, "c2", Action::Werewolf { player: "p3".to_string() }).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::End);
+    assert_eq!(game_state.phase.data["winner"], "Lovers");
+}
+
+#[test]
+fn cupid_chooses_two_living_players() {
+    let game_state = engine::apply(first_day(), "c1", Action::Sleep {}).unwrap();
+    assert_eq!(choose(game_state.clone(), "c4", &["p4", "p5"]).unwrap_err().code(), ErrorCode::WrongRole);
+    assert_eq!(choose(game_state.clone(), "c3", &["p4", "p4"]).unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert_eq!(choose(game_state.clone(), "c3", &["p1", "p4"]).unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert_eq!(choose(game_state.clone(), "c3", &["p4"]).unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert!(choose(game_state, "c3", &["p3", "p4"]).is_ok());
+    assert_eq!(choose(first_day(), "c3", &["p4", "p5"]).unwrap_err().code(), ErrorCode::NotYourTurn);
+}
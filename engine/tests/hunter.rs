@@ -0,0 +1,68 @@
+use common::{ErrorCode, PhaseName, PlayerRole};
+use engine::Action;
+
+mod support;
+use support::{alive, day, night, village};
+
+/// Enough villagers that a single death does not end the game.
+fn roles() -> Vec<PlayerRole> {
+    village(PlayerRole::Hunter, 4)
+}
+
+fn shoot(game_state: common::GameState, actor: &str, player: &str) -> Result<common::GameState, engine::RuleError> {
+    engine::apply(game_state, actor, Action::Hunter { player: player.to_string() })
+}
+
+#[test]
+fn the_hunter_shoots_when_eaten() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p3".to_string() }).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Hunter);
+    assert_eq!(game_state.phase.data["hunter"], "p3");
+    assert!(!alive(&game_state, "p3"));
+
+    let game_state = shoot(game_state, "c3", "p4").unwrap();
+    assert!(!alive(&game_state, "p4"));
+    assert_eq!(game_state.phase.name, PhaseName::Day);
+}
+
+#[test]
+fn the_hunter_shoots_when_lynched() {
+    let game_state = engine::apply(day(&roles()), "c1", Action::Lynch { player: "p3".to_string() }).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Hunter);
+    assert_eq!(game_state.phase.data["next"], "Night");
+
+    let game_state = shoot(game_state, "c3", "p2").unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::End);
+    assert_eq!(game_state.phase.data["winner"], "Good");
+}
+
+#[test]
+fn the_night_resumes_without_those_shot() {
+    let mut roles = roles();
+    roles[3] = PlayerRole::Seer;
+    let game_state = engine::apply(day(&roles), "c1", Action::Lynch { player: "p3".to_string() }).unwrap();
+    let game_state = shoot(game_state, "c3", "p4").unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Werewolf);
+}
+
+#[test]
+fn only_the_dead_hunter_shoots_the_living() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p3".to_string() }).unwrap();
+    assert_eq!(shoot(game_state.clone(), "c4", "p2").unwrap_err().code(), ErrorCode::WrongRole);
+    assert_eq!(shoot(game_state.clone(), "c3", "p3").unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert_eq!(shoot(game_state.clone(), "c3", "p1").unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert_eq!(engine::apply(game_state, "c1", Action::Sleep {}).unwrap_err().code(), ErrorCode::NotYourTurn);
+    assert_eq!(shoot(night(&roles()), "c3", "p2").unwrap_err().code(), ErrorCode::NotYourTurn);
+}
+
+#[test]
+fn a_dead_hunter_cannot_be_lynched_again() {
+    let game_state = engine::apply(day(&roles()), "c1", Action::Lynch { player: "p3".to_string() }).unwrap();
+    let mut game_state = shoot(game_state, "c3", "p4").unwrap();
+    game_state.phase.name = PhaseName::Day;
+
+    let lynch = |player: &str| engine::apply(game_state.clone(), "c1", Action::Lynch { player: player.to_string() });
+    assert_eq!(lynch("p3").unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert_eq!(lynch("p1").unwrap_err().code(), ErrorCode::InvalidTarget);
+    assert_eq!(lynch("p5").unwrap().phase.name, PhaseName::Werewolf);
+}
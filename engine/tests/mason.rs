@@ -0,0 +1,37 @@
+use common::{view_for, ErrorCode, GameState, PlayerRole, PlayerTeam};
+use engine::{Action, RuleError};
+
+mod support;
+
+/// Start a game for a lobby of `players` with one werewolf and `masons` masons.
+fn start(players: u32, masons: u32) -> Result<GameState, RuleError> {
+    support::start(players, Action::Start { werewolves: 1, bodyguard: None, seer: Some(false), lycan: None, tanner: None,
+        witch: None, hunter: None, cupid: None, minion: None, masons: Some(masons) })
+}
+
+#[test]
+fn masons_see_each_other() {
+    let game_state = start(6, 2).unwrap();
+    let masons: Vec<usize> = (0..game_state.players.len())
+        .filter(|i| game_state.players[*i].attributes.role == PlayerRole::Mason)
+        .collect();
+    assert_eq!(masons.len(), 2);
+    assert!(game_state.players.iter().filter(|p| p.attributes.role == PlayerRole::Mason).all(|p| p.attributes.team == PlayerTeam::Good));
+
+    let view = view_for(&game_state, &game_state.players[masons[0]]);
+    assert_eq!(view.players[masons[1]].attributes.role, PlayerRole::Mason);
+    let villager = game_state.players.iter().find(|p| p.attributes.role == PlayerRole::Villager).unwrap();
+    let view = view_for(&game_state, villager);
+    assert_eq!(view.players[masons[0]].attributes.role, PlayerRole::Unknown);
+}
+
+#[test]
+fn masons_come_in_pairs_or_threes() {
+    assert_eq!(start(6, 1).unwrap_err().code(), ErrorCode::InvalidRequest);
+    assert_eq!(start(7, 4).unwrap_err().code(), ErrorCode::InvalidRequest);
+    assert_eq!(start(4, 3).unwrap_err().code(), ErrorCode::TooManyRoles);
+    let game_state = start(5, 0).unwrap();
+    assert!(game_state.players.iter().all(|p| p.attributes.role != PlayerRole::Mason));
+    let game_state = start(6, 3).unwrap();
+    assert_eq!(game_state.players.iter().filter(|p| p.attributes.role == PlayerRole::Mason).count(), 3);
+}
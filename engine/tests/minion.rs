@@ -0,0 +1,66 @@
+use common::{view_for, ErrorCode, PhaseName, PlayerRole, PlayerTeam};
+use engine::Action;
+
+mod support;
+use support::{alive, night, start, village};
+
+fn roles() -> Vec<PlayerRole> {
+    let mut roles = village(PlayerRole::Minion, 3);
+    roles[3] = PlayerRole::Seer;
+    roles
+}
+
+#[test]
+fn the_minion_sees_the_werewolves_but_not_the_reverse() {
+    let game_state = start(5, Action::Start { werewolves: 1, bodyguard: None, seer: Some(false), lycan: None, tanner: None,
+        witch: None, hunter: None, cupid: None, minion: Some(true), masons: None }).unwrap();
+    let role_of = |role: PlayerRole| game_state.players.iter().position(|p| p.attributes.role == role).unwrap();
+    let (werewolf, minion) = (role_of(PlayerRole::Werewolf), role_of(PlayerRole::Minion));
+    assert_eq!(game_state.players[minion].attributes.team, PlayerTeam::Evil);
+
+    let view = view_for(&game_state, &game_state.players[minion]);
+    assert_eq!(view.players[werewolf].attributes.role, PlayerRole::Werewolf);
+    let view = view_for(&game_state, &game_state.players[werewolf]);
+    assert_eq!(view.players[minion].attributes.role, PlayerRole::Unknown);
+    assert_eq!(view.players[minion].attributes.team, PlayerTeam::Unknown);
+}
+
+#[test]
+fn the_minion_does_not_vote() {
+    let err = engine::apply(night(&roles()), "c3", Action::Werewolf { player: "p5".to_string() }).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::WrongRole);
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p5".to_string() }).unwrap();
+    assert!(!alive(&game_state, "p5"));
+    assert_eq!(game_state.phase.name, PhaseName::Day);
+}
+
+#[test]
+fn the_werewolves_can_eat_the_minion() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p3".to_string() }).unwrap();
+    assert!(!alive(&game_state, "p3"));
+    let err = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p2".to_string() }).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidTarget);
+}
+
+#[test]
+fn the_minion_appears_evil_to_the_seer() {
+    let mut game_state = night(&roles());
+    game_state.phase.name = PhaseName::Seer;
+    let game_state = engine::apply(game_state, "c4", Action::Seer { player: Some("p3".to_string()) }).unwrap();
+    let seer = game_state.players.iter().find(|p| p.id == "p4").unwrap();
+    let view = view_for(&game_state, seer);
+    let minion = view.players.iter().find(|p| p.id == "p3").unwrap();
+    assert_eq!(minion.attributes.role, PlayerRole::Unknown);
+    assert_eq!(minion.attributes.team, PlayerTeam::Evil);
+}
+
+#[test]
+fn only_the_werewolves_count_for_parity() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Day);
+
+    let game_state = engine::apply(game_state, "c1", Action::Lynch { player: "p2".to_string() }).unwrap();
+    assert!(alive(&game_state, "p3"));
+    assert_eq!(game_state.phase.name, PhaseName::End);
+    assert_eq!(game_state.phase.data["winner"], "Good");
+}
@@ -0,0 +1,27 @@
+use common::{ErrorCode, PhaseName, PlayerRole};
+use engine::Action;
+
+mod support;
+use support::{lobby, night, start};
+
+fn one_werewolf() -> Action {
+    Action::Start { werewolves: 1, bodyguard: None, seer: None, lycan: None, tanner: None, witch: None, hunter: None,
+        cupid: None, minion: None, masons: None }
+}
+
+#[test]
+fn only_players_start_the_game() {
+    let game_state = lobby(4);
+    let err = engine::apply(game_state.clone(), "c5", one_werewolf()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::PlayerNotFound);
+    assert_eq!(engine::apply(game_state, "c1", one_werewolf()).unwrap().phase.name, PhaseName::Day);
+}
+
+#[test]
+fn a_started_game_cannot_be_started_again() {
+    let game_state = start(4, one_werewolf()).unwrap();
+    assert_eq!(engine::apply(game_state, "c2", one_werewolf()).unwrap_err().code(), ErrorCode::NotYourTurn);
+
+    let game_state = night(&[PlayerRole::Mod, PlayerRole::Werewolf, PlayerRole::Villager, PlayerRole::Villager]);
+    assert_eq!(engine::apply(game_state, "c2", one_werewolf()).unwrap_err().code(), ErrorCode::NotYourTurn);
+}
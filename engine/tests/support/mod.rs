@@ -0,0 +1,72 @@
+//! Games set up for the rule tests, with roles dealt by hand rather than at random.
+
+// Each test binary includes this module and uses only some of it.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use common::{GameState, Phase, PhaseName, Player, PlayerAttributes, PlayerRole, PlayerTeam};
+use engine::{Action, RuleError};
+
+/// Roles for a game with `role` on `c3`, a lone werewolf on `c2` and
+/// `villagers` villagers after them.
+pub fn village(role: PlayerRole, villagers: usize) -> Vec<PlayerRole> {
+    let mut roles = vec![PlayerRole::Mod, PlayerRole::Werewolf, role];
+    roles.resize(3 + villagers, PlayerRole::Villager);
+    roles
+}
+
+/// A game on its first night, with a player on connection `c<n>` and ID
+/// `p<n>` for each of `roles`, numbered from 1.
+pub fn night(roles: &[PlayerRole]) -> GameState {
+    GameState {
+        lobby_id: "ABCD".to_string(),
+        phase: Phase { name: PhaseName::Werewolf, data: HashMap::new() },
+        players: roles.iter().enumerate().map(|(i, role)| Player {
+            id: format!("p{}", i + 1),
+            connection_id: format!("c{}", i + 1),
+            name: format!("Player {}", i + 1),
+            secret: String::new(),
+            attributes: PlayerAttributes {
+                role: role.clone(),
+                team: match role {
+                    PlayerRole::Mod => PlayerTeam::Mod,
+                    PlayerRole::Werewolf | PlayerRole::Minion => PlayerTeam::Evil,
+                    _ => PlayerTeam::Good,
+                },
+                alive: true,
+                visible_to: vec![],
+            },
+            online: true,
+            last_seen: 0,
+        }).collect(),
+        internal_state: HashMap::new(),
+        ttl: 0,
+        version: 1,
+    }
+}
+
+/// The same game as `night`, on the day before it.
+pub fn day(roles: &[PlayerRole]) -> GameState {
+    let mut game_state = night(roles);
+    game_state.phase.name = PhaseName::Day;
+    game_state
+}
+
+/// A lobby of `players` on `c1` to `c<players>`, waiting to start.
+pub fn lobby(players: u32) -> GameState {
+    let mut game_state = engine::new_game("ABCD".to_string(), "c1", "Player 1".to_string(), "s".to_string(), 0).unwrap();
+    for n in 2..=players {
+        game_state = engine::apply(game_state, &format!("c{}", n), Action::Join { name: format!("Player {}", n), secret: "s".to_string() }).unwrap();
+    }
+    game_state
+}
+
+/// Deal roles with `start` to a lobby of `players`, the first of whom moderates.
+pub fn start(players: u32, start: Action) -> Result<GameState, RuleError> {
+    engine::apply(lobby(players), "c1", start)
+}
+
+pub fn alive(game_state: &GameState, id: &str) -> bool {
+    game_state.players.iter().find(|p| p.id == id).unwrap().attributes.alive
+}
@@ -0,0 +1,293 @@
+//! Generative checks that no player's view of a game gives away anything the
+//! rules keep hidden from them, however the game has played out.
+
+use std::collections::{HashMap, HashSet};
+
+use proptest::prelude::*;
+use serde_json::Value;
+
+use common::{view_for, GameState, Phase, PhaseName, Player, PlayerAttributes, PlayerRole, PlayerTeam};
+use engine::Action;
+
+/// The roles dealt when the game starts.
+#[derive(Debug, Clone)]
+struct Roles {
+    werewolves: u32,
+    bodyguard: bool,
+    seer: bool,
+    lycan: bool,
+    tanner: bool,
+    witch: bool,
+    hunter: bool,
+    cupid: bool,
+    minion: bool,
+    masons: u32,
+}
+
+impl Roles {
+    /// Players needed to deal these roles, counting the moderator.
+    fn needed(&self) -> usize {
+        1 + self.werewolves as usize + self.masons as usize + [self.bodyguard, self.seer, self.lycan, self.tanner, self.witch, self.hunter, self.cupid, self.minion].iter().filter(|r| **r).count()
+    }
+}
+
+/// A lobby of players with the given secrets, the first of whom will moderate.
+fn lobby(secrets: &[String]) -> GameState {
+    GameState {
+        lobby_id: "ABCD".to_string(),
+        phase: Phase { name: PhaseName::Lobby, data: HashMap::new() },
+        players: secrets.iter().enumerate().map(|(i, secret)| Player {
+            id: format!("p{}", i + 1),
+            connection_id: format!("connection-{}", i + 1),
+            name: format!("Player {}", i + 1),
+            secret: secret.clone(),
+            attributes: PlayerAttributes {
+                role: PlayerRole::Unknown,
+                team: PlayerTeam::Unknown,
+                alive: true,
+                visible_to: vec!["All".to_string()],
+            },
+            online: true,
+            last_seen: 0,
+        }).collect(),
+        internal_state: HashMap::new(),
+        ttl: 0,
+        version: 1,
+    }
+}
+
+/// One move in a random game: whether to pass up the chance to act (the
+/// moderator ending the day, the seer not checking anyone, the witch poisoning
+/// rather than healing), and which actor and target to pick among the living
+/// players. Cupid uses the actor choice as their second lover.
+type Step = (bool, usize, usize);
+
+/// Take the action the current phase calls for as described by `step`.
+/// Returns the new state, or `None` if there was nothing to do or the rules
+/// rejected it.
+fn step(game_state: &GameState, (pass, choice, target): Step) -> Option<GameState> {
+    let living: Vec<&Player> = game_state.players.iter()
+        .filter(|p| p.attributes.alive && p.attributes.role != PlayerRole::Mod)
+        .collect();
+    if living.is_empty() {
+        return None;
+    }
+    let with_role = |role: PlayerRole| -> Vec<String> {
+        living.iter().filter(|p| p.attributes.role == role).map(|p| p.connection_id.clone()).collect()
+    };
+    let target = living[target % living.len()].id.clone();
+    let moderator = game_state.players[0].connection_id.clone();
+
+    let (actors, action) = match game_state.phase.name {
+        PhaseName::Day if pass => (vec![moderator], Action::Sleep {}),
+        PhaseName::Day => (vec![moderator], Action::Lynch { player: target }),
+        PhaseName::Cupid => {
+            let second = living[choice % living.len()].id.clone();
+            (with_role(PlayerRole::Cupid), Action::Cupid { players: vec![target, second] })
+        },
+        PhaseName::Seer => (with_role(PlayerRole::Seer), Action::Seer { player: Some(target).filter(|_| !pass) }),
+        PhaseName::Bodyguard => (with_role(PlayerRole::Bodyguard), Action::Bodyguard { player: target }),
+        PhaseName::Werewolf => (with_role(PlayerRole::Werewolf), Action::Werewolf { player: target }),
+        PhaseName::Witch => (with_role(PlayerRole::Witch), Action::Witch { heal: !pass, poison: Some(target).filter(|_| pass) }),
+        PhaseName::Hunter => {
+            let hunter = game_state.players.iter().find(|p| Some(&p.id) == game_state.phase.data.get("hunter"))?;
+            (vec![hunter.connection_id.clone()], Action::Hunter { player: target })
+        },
+        PhaseName::Lobby | PhaseName::End => return None,
+    };
+    let actor = actors.get(choice % actors.len().max(1))?;
+    engine::apply(game_state.clone(), actor, action).ok()
+}
+
+/// Start a game with `roles` and play `steps` through it, returning every
+/// state it passed through.
+fn play(secrets: &[String], roles: &Roles, steps: &[Step]) -> Vec<GameState> {
+    let mut game_state = lobby(secrets);
+    let start = Action::Start {
+        werewolves: roles.werewolves,
+        bodyguard: Some(roles.bodyguard),
+        seer: Some(roles.seer),
+        lycan: Some(roles.lycan),
+        tanner: Some(roles.tanner),
+        witch: Some(roles.witch),
+        hunter: Some(roles.hunter),
+        cupid: Some(roles.cupid),
+        minion: Some(roles.minion),
+        masons: Some(roles.masons),
+    };
+    game_state = engine::apply(game_state, "connection-1", start).expect("roles fit the lobby");
+
+    let mut states = vec![game_state.clone()];
+    for s in steps {
+        if let Some(next) = step(&game_state, *s) {
+            game_state = next;
+            states.push(game_state.clone());
+        }
+    }
+    states
+}
+
+/// Roles for a lobby, with the seer and lycan forced in when asked for.
+fn roles(seer: bool, lycan: bool) -> impl Strategy<Value = Roles> {
+    (1..=3u32, any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>(),
+        prop_oneof![Just(0), 2..=3u32])
+        .prop_map(move |(werewolves, bodyguard, any_seer, any_lycan, tanner, witch, hunter, cupid, minion, masons)| Roles {
+            werewolves,
+            bodyguard,
+            seer: seer || any_seer,
+            lycan: lycan || any_lycan,
+            tanner,
+            witch,
+            hunter,
+            cupid,
+            minion,
+            masons,
+        })
+}
+
+/// Everything needed to play a random game: player secrets, the roles dealt
+/// and the choices made at each step.
+fn games(seer: bool, lycan: bool) -> impl Strategy<Value = (Vec<String>, Roles, Vec<Step>)> {
+    roles(seer, lycan).prop_flat_map(|roles| {
+        let secrets = prop::collection::vec("secret-[a-z0-9]{12}", roles.needed()..=16);
+        let steps = prop::collection::vec((prop::bool::weighted(0.25), any::<usize>(), any::<usize>()), 0..40);
+        (secrets, Just(roles), steps)
+    })
+}
+
+/// Every string in `value`, keys included.
+fn strings<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => found.push(s),
+        Value::Array(values) => values.iter().for_each(|v| strings(v, found)),
+        Value::Object(map) => map.iter().for_each(|(k, v)| {
+            found.push(k);
+            strings(v, found);
+        }),
+        _ => {},
+    }
+}
+
+fn was_seen_by_seer(player: &Player) -> bool {
+    player.attributes.visible_to.contains(&format!("{:?}", PlayerRole::Seer))
+}
+
+/// Whether `player`'s role would be hidden from `viewer` outside the end of
+/// the game, unless their roles let them see it.
+fn is_hidden(game_state: &GameState, viewer: &Player, player: &Player) -> bool {
+    game_state.phase.name != PhaseName::End && player.id != viewer.id && player.attributes.alive
+        && player.attributes.role != PlayerRole::Mod && !is_lover(game_state, viewer, player)
+}
+
+fn is_lover(game_state: &GameState, viewer: &Player, player: &Player) -> bool {
+    matches!(game_state.lovers(), Some((a, b)) if (a == viewer.id && b == player.id) || (a == player.id && b == viewer.id))
+}
+
+proptest! {
+    #[test]
+    fn no_view_contains_a_secret((secrets, roles, steps) in games(false, false)) {
+        for game_state in play(&secrets, &roles, &steps) {
+            let hidden: HashSet<&str> = game_state.players.iter()
+                .flat_map(|p| vec![p.secret.as_str(), p.connection_id.as_str()])
+                .collect();
+            for viewer in &game_state.players {
+                let view = serde_json::to_value(view_for(&game_state, viewer)).unwrap();
+                let mut found = vec![];
+                strings(&view, &mut found);
+                for s in found {
+                    prop_assert!(!hidden.contains(s) && !s.contains("secret-"), "{} leaked in {}", s, view);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn villagers_never_see_living_werewolves((secrets, roles, steps) in games(false, false)) {
+        for game_state in play(&secrets, &roles, &steps) {
+            for viewer in game_state.players.iter().filter(|p| p.attributes.role == PlayerRole::Villager) {
+                let view = view_for(&game_state, viewer);
+                for (seen, player) in view.players.iter().zip(&game_state.players) {
+                    if is_hidden(&game_state, viewer, player) && player.attributes.role == PlayerRole::Werewolf {
+                        prop_assert_eq!(&seen.attributes.role, &PlayerRole::Unknown);
+                        prop_assert_eq!(&seen.attributes.team, &PlayerTeam::Unknown);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_werewolves_and_masons_see_each_other((secrets, roles, steps) in games(false, false)) {
+        for game_state in play(&secrets, &roles, &steps) {
+            for viewer in game_state.players.iter().filter(|p| ![PlayerRole::Mod, PlayerRole::Seer].contains(&p.attributes.role)) {
+                let view = view_for(&game_state, viewer);
+                for (seen, player) in view.players.iter().zip(&game_state.players) {
+                    if !is_hidden(&game_state, viewer, player) {
+                        continue;
+                    }
+                    let pack = [PlayerRole::Werewolf, PlayerRole::Minion].contains(&viewer.attributes.role) &&
+                        player.attributes.role == PlayerRole::Werewolf;
+                    let masons = viewer.attributes.role == PlayerRole::Mason && player.attributes.role == PlayerRole::Mason;
+                    let expected = if pack || masons { &player.attributes.role } else { &PlayerRole::Unknown };
+                    prop_assert_eq!(&seen.attributes.role, expected);
+                }
+                if game_state.phase.name == PhaseName::Werewolf && viewer.attributes.role != PlayerRole::Werewolf {
+                    prop_assert!(view.phase.data.is_empty(), "werewolf votes shown to {:?}", viewer.attributes.role);
+                }
+                if game_state.phase.name == PhaseName::Witch && viewer.attributes.role != PlayerRole::Witch {
+                    prop_assert!(view.phase.data.is_empty(), "werewolf victim shown to {:?}", viewer.attributes.role);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lovers_see_each_other((secrets, roles, steps) in games(false, false)) {
+        for game_state in play(&secrets, &roles, &steps) {
+            for viewer in &game_state.players {
+                let view = view_for(&game_state, viewer);
+                for (seen, player) in view.players.iter().zip(&game_state.players) {
+                    if is_lover(&game_state, viewer, player) {
+                        prop_assert_eq!(&seen.attributes.role, &player.attributes.role);
+                        prop_assert_eq!(&seen.attributes.team, &player.attributes.team);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_seer_only_learns_teams_of_players_they_checked((secrets, roles, steps) in games(true, false)) {
+        for game_state in play(&secrets, &roles, &steps) {
+            for viewer in game_state.players.iter().filter(|p| p.attributes.role == PlayerRole::Seer) {
+                let view = view_for(&game_state, viewer);
+                for (seen, player) in view.players.iter().zip(&game_state.players) {
+                    if !is_hidden(&game_state, viewer, player) {
+                        continue;
+                    }
+                    prop_assert_eq!(&seen.attributes.role, &PlayerRole::Unknown);
+                    if !was_seen_by_seer(player) {
+                        prop_assert_eq!(&seen.attributes.team, &PlayerTeam::Unknown);
+                    }
+                    else if player.attributes.role != PlayerRole::Lycan {
+                        prop_assert_eq!(&seen.attributes.team, &player.attributes.team);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lycans_appear_evil_to_the_seer((secrets, roles, steps) in games(true, true)) {
+        for game_state in play(&secrets, &roles, &steps) {
+            for viewer in game_state.players.iter().filter(|p| p.attributes.role == PlayerRole::Seer) {
+                let view = view_for(&game_state, viewer);
+                for (seen, player) in view.players.iter().zip(&game_state.players) {
+                    if is_hidden(&game_state, viewer, player) && player.attributes.role == PlayerRole::Lycan && was_seen_by_seer(player) {
+                        prop_assert_eq!(&seen.attributes.team, &PlayerTeam::Evil);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use common::{GameState, Phase, PhaseName, PlayerRole};
+use engine::Action;
+
+mod support;
+use support::{alive, night, village};
+
+fn roles() -> Vec<PlayerRole> {
+    village(PlayerRole::Witch, 3)
+}
+
+fn witch(game_state: GameState, heal: bool, poison: Option<&str>) -> Result<GameState, engine::RuleError> {
+    engine::apply(game_state, "c3", Action::Witch { heal, poison: poison.map(|p| p.to_string()) })
+}
+
+#[test]
+fn the_witch_is_shown_the_victim() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Witch);
+    assert_eq!(game_state.phase.data["victim"], "p4");
+    assert_eq!(game_state.phase.data["potions"], "heal,poison");
+    assert!(alive(&game_state, "p4"));
+
+    let view = common::view_for(&game_state, &game_state.players[3]);
+    assert!(view.phase.data.is_empty());
+}
+
+#[test]
+fn healing_saves_the_victim_once() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    let game_state = witch(game_state, true, None).unwrap();
+    assert_eq!(game_state.phase.name, PhaseName::Day);
+    assert!(alive(&game_state, "p4"));
+
+    let mut game_state = game_state;
+    game_state.phase = Phase { name: PhaseName::Werewolf, data: HashMap::new() };
+    let game_state = engine::apply(game_state, "c2", Action::Werewolf { player: "p5".to_string() }).unwrap();
+    assert_eq!(game_state.phase.data["potions"], "poison");
+    assert_eq!(witch(game_state.clone(), true, None).unwrap_err().code(), common::ErrorCode::InvalidTarget);
+    let game_state = witch(game_state, false, None).unwrap();
+    assert!(!alive(&game_state, "p5"));
+}
+
+#[test]
+fn healing_a_guarded_victim_keeps_the_potion() {
+    let mut roles = roles();
+    roles[5] = PlayerRole::Bodyguard;
+    let mut game_state = night(&roles);
+    game_state.internal_state.insert("last_guarded".to_string(), "p4".to_string());
+    let game_state = engine::apply(game_state, "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    let mut game_state = witch(game_state, true, None).unwrap();
+    assert!(alive(&game_state, "p4"));
+
+    game_state.phase = Phase { name: PhaseName::Werewolf, data: HashMap::new() };
+    let game_state = engine::apply(game_state, "c2", Action::Werewolf { player: "p5".to_string() }).unwrap();
+    assert_eq!(game_state.phase.data["potions"], "heal,poison");
+}
+
+#[test]
+fn poison_kills_another_player_once() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    assert_eq!(witch(game_state.clone(), false, Some("p1")).unwrap_err().code(), common::ErrorCode::InvalidTarget);
+    assert_eq!(witch(game_state.clone(), false, Some("p3")).unwrap_err().code(), common::ErrorCode::InvalidTarget);
+
+    let game_state = witch(game_state, false, Some("p2")).unwrap();
+    assert!(!alive(&game_state, "p4"));
+    assert!(!alive(&game_state, "p2"));
+    assert_eq!(game_state.phase.name, PhaseName::End);
+    assert_eq!(game_state.phase.data["winner"], "Good");
+}
+
+#[test]
+fn only_the_witch_acts_in_her_phase() {
+    let game_state = engine::apply(night(&roles()), "c2", Action::Werewolf { player: "p4".to_string() }).unwrap();
+    let err = engine::apply(game_state.clone(), "c4", Action::Witch { heal: true, poison: None }).unwrap_err();
+    assert_eq!(err.code(), common::ErrorCode::WrongRole);
+    assert_eq!(witch(night(&roles()), true, None).unwrap_err().code(), common::ErrorCode::NotYourTurn);
+}
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+
+use common::notifier::{Notifier, NotifyError};
+
+/// The open websocket connections, playing the part of the API Gateway
+/// management API for the self-hosted server.
+#[derive(Default)]
+pub struct Connections {
+    next_id: AtomicUsize,
+    senders: Mutex<HashMap<String, UnboundedSender<Message>>>,
+}
+
+impl Connections {
+    pub fn new() -> Connections {
+        Connections::default()
+    }
+
+    /// Register a connection's outgoing channel, returning its new connection ID.
+    pub fn add(&self, sender: UnboundedSender<Message>) -> String {
+        let connection_id = format!("connection-{}", self.next_id.fetch_add(1, Ordering::SeqCst));
+        self.senders.lock().unwrap().insert(connection_id.clone(), sender);
+        connection_id
+    }
+
+    pub fn remove(&self, connection_id: &str) {
+        self.senders.lock().unwrap().remove(connection_id);
+    }
+}
+
+impl Notifier for Connections {
+    fn send(&self, connection_id: &str, message: &Value) -> Result<(), NotifyError> {
+        match self.senders.lock().unwrap().get(connection_id) {
+            Some(sender) => sender.send(Message::Text(message.to_string())).map_err(|_| NotifyError::Gone),
+            None => Err(NotifyError::Gone),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+#[macro_use]
+extern crate log;
+extern crate simple_logger;
+extern crate tokio;
+
+use std::env;
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::unbounded_channel;
+use tokio_tungstenite::tungstenite::Message;
+
+use simple_logger::SimpleLogger;
+use log::LevelFilter;
+
+use common::session::SessionSigner;
+use common::store::MemoryStore;
+
+mod connections;
+use connections::Connections;
+
+mod store;
+use store::BroadcastingStore;
+
+type ServerError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+type Store = BroadcastingStore<MemoryStore>;
+
+#[tokio::main]
+async fn main() -> Result<(), ServerError> {
+    SimpleLogger::new().with_level(LevelFilter::Info).init()?;
+    let address = env::var("bindAddress").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
+
+    let signer = Arc::new(session_signer());
+
+    let connections = Arc::new(Connections::new());
+    let store = Arc::new(BroadcastingStore::new(MemoryStore::new(), connections.clone()));
+
+    let mut listener = TcpListener::bind(&address).await?;
+    info!("Listening on {}", address);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        info!("New connection from {}", peer);
+        tokio::spawn(handle_connection(stream, store.clone(), connections.clone(), signer.clone()));
+    }
+}
+
+/// Sign session tokens with the `sessionKey` environment variable, or a
+/// random key when it is unset. Games do not outlive the server, so neither
+/// need their tokens.
+fn session_signer() -> SessionSigner {
+    match env::var("sessionKey") {
+        Ok(key) if !key.is_empty() => SessionSigner::new(key.as_bytes()),
+        _ => {
+            let key: [u8; 32] = rand::random();
+            SessionSigner::new(&key)
+        },
+    }
+}
+
+async fn handle_connection(stream: TcpStream, store: Arc<Store>, connections: Arc<Connections>, signer: Arc<SessionSigner>) {
+    let socket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(socket) => socket,
+        Err(err) => {
+            error!("Error during websocket handshake: {:?}", err);
+            return;
+        },
+    };
+    let (mut outgoing, mut incoming) = socket.split();
+
+    let (sender, mut receiver) = unbounded_channel();
+    let connection_id = connections.add(sender);
+    tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            if let Err(err) = outgoing.send(message).await {
+                error!("Error sending message: {:?}", err);
+                break;
+            }
+        }
+    });
+
+    while let Some(message) = incoming.next().await {
+        match message {
+            Ok(Message::Text(body)) => {
+                // Joining hashes the player's secret with Argon2, which is too
+                // slow to run on one of the runtime's worker threads.
+                let (store, connections, signer, connection_id) = (store.clone(), connections.clone(), signer.clone(), connection_id.clone());
+                let handled = tokio::task::spawn_blocking(move || {
+                    api::handle_request(store.as_ref(), connections.as_ref(), signer.as_ref(), &connection_id, Some(&body))
+                }).await;
+                if let Err(err) = handled {
+                    error!("Error handling request: {:?}", err);
+                }
+            },
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {},
+            Err(err) => {
+                error!("Error reading from connection {}: {:?}", connection_id, err);
+                break;
+            },
+        }
+    }
+    connections.remove(&connection_id);
+    let handled = tokio::task::spawn_blocking(move || api::handle_disconnect(store.as_ref(), &connection_id)).await;
+    if let Err(err) = handled {
+        error!("Error handling disconnect: {:?}", err);
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use common::GameState;
+use common::broadcast::broadcast_state;
+use common::store::{ConnectionEntry, GameStore, StoreError};
+
+use crate::connections::Connections;
+
+/// Wraps a `GameStore` so that every successful write pushes the new state
+/// straight to the players in the game, standing in for the DynamoDB stream
+/// and the broadcast lambda.
+pub struct BroadcastingStore<S: GameStore> {
+    store: S,
+    connections: Arc<Connections>,
+}
+
+impl<S: GameStore> BroadcastingStore<S> {
+    pub fn new(store: S, connections: Arc<Connections>) -> BroadcastingStore<S> {
+        BroadcastingStore { store, connections }
+    }
+}
+
+impl<S: GameStore> GameStore for BroadcastingStore<S> {
+    fn create(&self, game_state: &GameState) -> Result<(), StoreError> {
+        self.store.create(game_state)?;
+        broadcast_state(self.connections.as_ref(), game_state);
+        Ok(())
+    }
+
+    fn load(&self, lobby_id: &str) -> Result<GameState, StoreError> {
+        self.store.load(lobby_id)
+    }
+
+    fn save(&self, game_state: GameState) -> Result<GameState, StoreError> {
+        let game_state = self.store.save(game_state)?;
+        broadcast_state(self.connections.as_ref(), &game_state);
+        Ok(game_state)
+    }
+
+    fn bind_connection(&self, entry: &ConnectionEntry) -> Result<(), StoreError> {
+        self.store.bind_connection(entry)
+    }
+
+    fn find_connection(&self, connection_id: &str) -> Result<Option<ConnectionEntry>, StoreError> {
+        self.store.find_connection(connection_id)
+    }
+
+    fn unbind_connection(&self, connection_id: &str) -> Result<(), StoreError> {
+        self.store.unbind_connection(connection_id)
+    }
+}
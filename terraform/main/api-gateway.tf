@@ -14,13 +14,25 @@ resource "aws_apigatewayv2_route" "default_route" {
   target    = "integrations/${aws_apigatewayv2_integration.default_route_integration.id}"
 }
 
+resource "aws_apigatewayv2_route" "connect_route" {
+  api_id    = aws_apigatewayv2_api.api.id
+  route_key = "$connect"
+  target    = "integrations/${aws_apigatewayv2_integration.default_route_integration.id}"
+}
+
+resource "aws_apigatewayv2_route" "disconnect_route" {
+  api_id    = aws_apigatewayv2_api.api.id
+  route_key = "$disconnect"
+  target    = "integrations/${aws_apigatewayv2_integration.default_route_integration.id}"
+}
+
 resource "aws_apigatewayv2_integration" "default_route_integration" {
   api_id           = aws_apigatewayv2_api.api.id
   integration_type = "AWS_PROXY"
 
   connection_type           = "INTERNET"
   content_handling_strategy = "CONVERT_TO_TEXT"
-  description               = "Integration for the default API route"
+  description               = "Integration for the default, connect and disconnect API routes"
   integration_uri           = module.api_lambda.invoke_arn
   passthrough_behavior      = "WHEN_NO_MATCH"
 }
@@ -51,6 +63,8 @@ resource "aws_apigatewayv2_deployment" "deployment" {
 
   depends_on = [
     aws_apigatewayv2_route.default_route,
+    aws_apigatewayv2_route.connect_route,
+    aws_apigatewayv2_route.disconnect_route,
     aws_apigatewayv2_integration.default_route_integration,
   ]
 }
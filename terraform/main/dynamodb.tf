@@ -23,6 +23,26 @@ resource "aws_dynamodb_table" "janky-werewolf-table" {
   }
 }
 
+resource "aws_dynamodb_table" "janky-werewolf-connections-table" {
+  name           = "${var.environment}-janky-werewolf-connections-table"
+  billing_mode   = "PAY_PER_REQUEST"
+  hash_key       = "connection_id"
+
+  attribute {
+    name = "connection_id"
+    type = "S"
+  }
+
+  ttl {
+    attribute_name = "ttl"
+    enabled        = true
+  }
+
+  tags = {
+    Environment = var.environment
+  }
+}
+
 resource "aws_lambda_event_source_mapping" "broadcast-state-mapping" {
   event_source_arn       = aws_dynamodb_table.janky-werewolf-table.stream_arn
   function_name          = module.broadcast_lambda.lambda_arn
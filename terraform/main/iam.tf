@@ -84,7 +84,8 @@ resource "aws_iam_policy" "dynamodb_read_write_policy" {
       ],
       "Effect": "Allow",
       "Resource": ["${aws_dynamodb_table.janky-werewolf-table.arn}",
-                   "${aws_dynamodb_table.janky-werewolf-table.arn}/index/*"]
+                   "${aws_dynamodb_table.janky-werewolf-table.arn}/index/*",
+                   "${aws_dynamodb_table.janky-werewolf-connections-table.arn}"]
     },
     {
       "Action": [
@@ -142,18 +143,24 @@ resource "aws_iam_policy" "cloudwatch_log_policy" {
 EOF
 }
 
-resource "aws_iam_role_policy_attachment" "cloudwatch_read_only_policy_attachment" {
-  role       = aws_iam_role.iam_for_lambda_read_only.name
+resource "aws_iam_role_policy_attachment" "cloudwatch_broadcast_policy_attachment" {
+  role       = aws_iam_role.iam_for_lambda_broadcast.name
   policy_arn = aws_iam_policy.cloudwatch_log_policy.arn
 }
 
-resource "aws_iam_role_policy_attachment" "stream_read_only_policy_attachment" {
-  role       = aws_iam_role.iam_for_lambda_read_only.name
+resource "aws_iam_role_policy_attachment" "stream_broadcast_policy_attachment" {
+  role       = aws_iam_role.iam_for_lambda_broadcast.name
   policy_arn = aws_iam_policy.dynamodb_stream_policy.arn
 }
 
-resource "aws_iam_role" "iam_for_lambda_read_only" {
-  name               = "${var.environment}-iam_for_lambda_read_only"
+# The broadcaster writes back to mark players on gone connections offline.
+resource "aws_iam_role_policy_attachment" "dynamodb_broadcast_policy_attachment" {
+  role       = aws_iam_role.iam_for_lambda_broadcast.name
+  policy_arn = aws_iam_policy.dynamodb_read_write_policy.arn
+}
+
+resource "aws_iam_role" "iam_for_lambda_broadcast" {
+  name               = "${var.environment}-iam_for_lambda_broadcast"
   assume_role_policy = data.aws_iam_policy_document.policy.json
 
   tags = {
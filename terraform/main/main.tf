@@ -7,6 +7,9 @@ provider "aws" {
 provider "archive" {
 }
 
+provider "random" {
+}
+
 terraform {
   backend "s3" {
     bucket         = "janky-werewolf-backend-terraform-state"
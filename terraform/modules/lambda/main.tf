@@ -17,8 +17,10 @@ resource "aws_lambda_function" "lambda" {
 
   environment {
     variables = {
-      tableName = var.table_name
-      apiUrl    = var.api_gateway_url
+      tableName            = var.table_name
+      connectionsTableName = var.connections_table_name
+      apiUrl               = var.api_gateway_url
+      sessionKey           = var.session_key
     }
   }
 
@@ -38,3 +38,16 @@ variable "table_name" {
   description = "The name of the dynamodb table to use in the lambda."
   type        = string
 }
+
+variable "connections_table_name" {
+  description = "The name of the dynamodb table mapping connections to games."
+  type        = string
+  default     = ""
+}
+
+variable "session_key" {
+  description = "The key used to sign session tokens for rejoining games."
+  type        = string
+  default     = ""
+  sensitive   = true
+}